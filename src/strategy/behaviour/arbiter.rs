@@ -0,0 +1,35 @@
+use crate::model::Unit;
+use crate::strategy::behaviour::behaviour::Behaviour;
+use crate::strategy::holder::{get_previous_behaviour, set_previous_behaviour};
+
+// bonus for the behaviour chosen on the previous tick, so units don't flip-flop
+const HYSTERESIS: f64 = 0.15;
+
+// picks the applicable behaviour with the highest utility, ties go to the earlier one
+pub fn choose_behaviour<'a>(
+    unit: &Unit,
+    behaviours: &[&'a dyn Behaviour],
+) -> Option<&'a dyn Behaviour> {
+    let previous = get_previous_behaviour(unit.id);
+    let chosen = behaviours
+        .iter()
+        .filter(|b| b.should_use(unit))
+        .map(|b| {
+            let bonus = if previous == Some(b.name()) {
+                HYSTERESIS
+            } else {
+                0.0
+            };
+            (*b, b.utility(unit) + bonus)
+        })
+        .rev()
+        .max_by(|(_, u1), (_, u2)| u1.total_cmp(u2))
+        .map(|(b, _)| b);
+    if previous != chosen.map(|b| b.name()) {
+        if let Some(left) = behaviours.iter().find(|b| Some(b.name()) == previous) {
+            left.on_deselect(unit);
+        }
+    }
+    set_previous_behaviour(unit.id, chosen.map(|b| b.name()));
+    chosen
+}
@@ -1,19 +1,18 @@
 use crate::debug_interface::DebugInterface;
 use crate::debugging::RED;
-use crate::model::ActionOrder::{Aim, Pickup, UseShieldPotion};
-use crate::model::Item::ShieldPotions;
-use crate::model::{ActionOrder, Loot, Unit, UnitOrder, Vec2};
-use crate::strategy::holder::{
-    get_all_enemy_units, get_constants, get_game, get_loot, get_obstacles, remove_loot,
-};
-use crate::strategy::loot::best_loot;
-use crate::strategy::util::intersects_with_obstacles;
-use itertools::Itertools;
-use std::cmp::min;
+use crate::model::{Unit, Vec2};
+use crate::strategy::behaviour::tree::Node;
+use crate::strategy::zone::{predicted_zone, ZONE_LOOKAHEAD_SECONDS};
 
 pub trait Behaviour: Sync {
+    fn name(&self) -> &'static str;
     fn should_use(&self, unit: &Unit) -> bool;
-    fn order(&self, unit: &Unit, debug_interface: &mut Option<&mut DebugInterface>) -> UnitOrder;
+    // how much the unit wants this behaviour, only asked when should_use is true
+    fn utility(&self, unit: &Unit) -> f64;
+    // subtree giving the unit its order while the behaviour is chosen
+    fn tree(&'static self) -> Box<dyn Node>;
+    // called once when the unit switches away from this behaviour
+    fn on_deselect(&self, _unit: &Unit) {}
 }
 
 pub fn write_behaviour(
@@ -40,8 +39,9 @@ pub fn write_behaviour(
 }
 
 pub fn zone_penalty(p: &Vec2) -> f64 {
-    let distance_to_zone_center = p.distance(&get_game().zone.current_center);
-    let zone_penalty_score = if distance_to_zone_center / &get_game().zone.current_radius > 0.9 {
+    let (center, radius) = predicted_zone(ZONE_LOOKAHEAD_SECONDS);
+    let distance_to_zone_center = p.distance(&center);
+    let zone_penalty_score = if distance_to_zone_center / radius > 0.9 {
         distance_to_zone_center * 50.0
     } else {
         0.0
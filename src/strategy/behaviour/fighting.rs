@@ -1,42 +1,45 @@
-use crate::debug_interface::DebugInterface;
-use crate::debugging::{BLUE, RED, TEAL, TRANSPARENT_BLUE, TRANSPARENT_TEAL};
-use crate::model::ActionOrder::Aim;
-use crate::model::{Obstacle, Unit, UnitOrder, Vec2, WeaponProperties};
-use crate::strategy::behaviour::behaviour::{
-    my_units_collision_score, my_units_magnet_score, write_behaviour, zone_penalty, Behaviour,
+use crate::model::ActionOrder::Pickup;
+use crate::model::{Obstacle, Unit, Vec2};
+use crate::strategy::behaviour::behaviour::{my_units_magnet_score, zone_penalty, Behaviour};
+use crate::strategy::behaviour::subtrees::{
+    aim_when, label, lead_target, move_and_act, step_to_goal, FIRE_TARGET, GOAL,
 };
-use crate::strategy::holder::fight_sim::FightSimResult;
-use crate::strategy::holder::{
-    get_all_enemy_units, get_constants, get_fight_simulations, get_game, get_obstacles,
+use crate::strategy::behaviour::tree::{
+    Action, Blackboard, Condition, Node, Selector, Sequence, SetPoint, Status,
 };
-use crate::strategy::util::{
-    bullet_trace_score, get_projectile_traces, intersects_with_obstacles,
-    intersects_with_obstacles_vec, intersects_with_units_vec,
+use crate::strategy::cover::cover_score;
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_fight_simulations, get_game, get_obstacles, get_target,
+    get_team_plan, remove_loot,
 };
-use itertools::{all, Itertools};
-use std::cmp::{max, min};
-use std::collections::HashMap;
-use std::fmt;
-use std::fmt::{Formatter, Pointer};
-use std::path::Display;
+use crate::strategy::shots::is_line_of_fire_clear;
+use crate::strategy::sustain::should_disengage;
+use crate::strategy::targeting::fightable_enemies;
+use crate::strategy::util::intersects_with_obstacles_vec;
+use crate::strategy::weapons::{can_swap_now, weapon_to_fetch};
+use itertools::Itertools;
+use std::cmp::max;
+
+const FLANK_WEIGHT: f64 = 0.3;
+const COVER_WEIGHT: f64 = 5.0;
+// enemies busy with another team are the cheapest to take
+const DISTRACTION_BONUS: f64 = 0.2;
 
 pub struct Fighting {}
 
 impl Behaviour for Fighting {
+    fn name(&self) -> &'static str {
+        "Fighting"
+    }
+
     fn should_use(&self, unit: &Unit) -> bool {
         if unit.action.is_some() {
             return false;
         };
-        let have_weapon_and_ammo = match unit.weapon {
-            None => false,
-            Some(weapon) => {
-                if get_game().current_tick < 6000 {
-                    weapon != 0 && unit.ammo[weapon as usize] != 0
-                } else {
-                    unit.ammo[weapon as usize] != 0
-                }
-            }
-        };
+        if should_disengage(unit) {
+            return false;
+        }
+        let have_weapon_and_ammo = unit.ammo_for_current_weapon() > 0;
         if !have_weapon_and_ammo {
             return false;
         };
@@ -54,11 +57,8 @@ impl Behaviour for Fighting {
                     && s.enemy_units()
                         .iter()
                         .any(|e| e.position.distance(&unit.position) <= e.firing_distance())
-                    && match s.result {
-                        FightSimResult::WON(_) => false,
-                        FightSimResult::DRAW => false,
-                        FightSimResult::LOST => get_game().current_tick < 6000,
-                    }
+                    && !s.is_favourable()
+                    && get_game().current_tick < 6000
             })
             .is_some();
 
@@ -66,107 +66,108 @@ impl Behaviour for Fighting {
             return false;
         }
 
-        let fight_sims = get_fight_simulations();
-        fight_sims
-            .into_iter()
-            .filter(|s| {
-                s.allies.contains(&unit.id)
-                    && match s.result {
-                        FightSimResult::WON(_) => true,
-                        FightSimResult::DRAW => true,
-                        FightSimResult::LOST => get_game().current_tick > 6000,
-                    }
-            })
-            .flat_map(|e| e.enemy_units())
-            .find(|e| e.position.distance(&unit.position) < unit.firing_distance())
-            .is_some()
+        fightable_enemies(unit)
+            .iter()
+            .any(|e| e.position.distance(&unit.position) < unit.firing_distance())
     }
 
-    fn order(&self, unit: &Unit, debug_interface: &mut Option<&mut DebugInterface>) -> UnitOrder {
-        write_behaviour(unit, "Fighting".to_owned(), debug_interface);
-
-        let game = get_game();
-        let constants = get_constants();
-        let weapon = &constants.weapons[unit.weapon.unwrap_or(0) as usize];
-        let traces = get_projectile_traces();
-        let fight_sims = get_fight_simulations();
-
-        let targets = fight_sims
-            .into_iter()
+    fn utility(&self, unit: &Unit) -> f64 {
+        let best_prospect = get_fight_simulations()
+            .iter()
+            .filter(|s| s.allies.contains(&unit.id))
             .filter(|s| {
-                s.allies.contains(&unit.id)
-                    && match s.result {
-                        FightSimResult::WON(_) => true,
-                        FightSimResult::DRAW => true,
-                        FightSimResult::LOST => get_game().current_tick > 6000,
-                    }
+                s.enemy_units()
+                    .iter()
+                    .any(|e| e.position.distance(&unit.position) < unit.firing_distance())
             })
-            .flat_map(|s| s.enemy_units())
-            .map(|e| (e, e.position.distance(&unit.position)))
-            .collect_vec();
-
-        let target = targets
-            .iter()
-            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-            .unwrap()
-            .0;
-
-        if let Some(debug) = debug_interface.as_mut() {
-            debug.add_circle(target.position.clone(), 0.5, RED.clone());
-        }
+            .map(|s| {
+                (s.result.win_probability + s.result.enemy_distraction * DISTRACTION_BONUS).min(1.0)
+            })
+            .fold(0.0, f64::max);
+        0.5 + 0.5 * best_prospect
+    }
 
-        let obstacles = &get_obstacles(unit.id);
-        let fire_target = target.position.clone()
-            + (target.velocity.clone() * unit.position.distance(&target.position)
-                / weapon.projectile_speed);
+    fn tree(&'static self) -> Box<dyn Node> {
+        Box::new(Sequence {
+            name: self.name(),
+            children: vec![
+                label(self.name()),
+                lead_target(),
+                Box::new(SetPoint {
+                    name: "Firing spot",
+                    key: GOAL,
+                    // a better weapon behind cover is worth a short break in the fight
+                    compute: Box::new(|bb| match weapon_to_fetch(bb.unit) {
+                        Some(loot) => Some(loot.position),
+                        None => fight_target(bb.unit).map(|target| {
+                            get_best_firing_spot(bb.unit, &target, &get_obstacles(bb.unit.id))
+                        }),
+                    }),
+                }),
+                move_and_act(
+                    step_to_goal(true),
+                    Box::new(Selector {
+                        name: "Act",
+                        children: vec![swap_weapon(), aim_when(Box::new(is_ready_to_aim))],
+                    }),
+                ),
+            ],
+        })
+    }
+}
 
-        let intersects_with_obstacles =
-            intersects_with_obstacles_vec(&unit.position, &fire_target, obstacles);
-        let intersects_with_friends =
-            intersects_with_units_vec(&unit.position, &fire_target, &unit.my_other_units());
-        let goal = get_best_firing_spot(unit, &target, obstacles);
+fn swap_weapon() -> Box<dyn Node> {
+    Box::new(Sequence {
+        name: "Swap weapon",
+        children: vec![
+            Box::new(Condition {
+                name: "Can swap",
+                check: Box::new(|bb| {
+                    weapon_to_fetch(bb.unit).is_some_and(|loot| can_swap_now(bb.unit, loot))
+                }),
+            }),
+            Box::new(Action {
+                name: "Pick up",
+                act: Box::new(|bb, _debug| {
+                    let loot = weapon_to_fetch(bb.unit).unwrap();
+                    unsafe { remove_loot(loot.id) };
+                    bb.order.action = Some(Pickup { loot: loot.id });
+                    Status::Success
+                }),
+            }),
+        ],
+    })
+}
 
-        let result_move = unit
-            .points_around_unit(true)
-            .iter()
-            .map(|p| {
-                (
-                    p,
-                    bullet_trace_score(&traces, &p)
-                        + my_units_collision_score(&p, unit)
-                        + p.distance(&goal),
-                )
-            })
-            .min_by(|e1, e2| f64::partial_cmp(&e1.1, &e2.1).unwrap())
-            .map(|e| e.0)
-            //TODO
-            .unwrap_or(&game.zone.current_center)
-            .clone();
-
-        if let Some(debug) = debug_interface.as_mut() {
-            debug.add_circle(result_move.clone(), 0.1, BLUE.clone());
-            debug.add_circle(goal.clone(), 1.0, TRANSPARENT_BLUE.clone());
-        }
+// the shot is ready by the time the aim is
+fn is_ready_to_aim(bb: &Blackboard) -> bool {
+    let unit = bb.unit;
+    let weapon = &get_constants().weapons[unit.weapon.unwrap_or(0) as usize];
+    let ticks_until_next_shot =
+        max(get_game().current_tick, unit.next_shot_tick) - get_game().current_tick;
+    bb.point(FIRE_TARGET)
+        .is_some_and(|p| p.distance(&unit.position) < weapon.firing_distance())
+        && ticks_until_next_shot as f64 <= weapon.ticks_to_aim() as f64 * (1.0 - unit.aim)
+        && !is_on_weapon_to_fetch(unit)
+}
 
-        let ticks_until_next_shot =
-            max(get_game().current_tick, unit.next_shot_tick) - get_game().current_tick;
-        let action = if fire_target.distance(&unit.position) < weapon.firing_distance()
-            && ticks_until_next_shot as f64 <= weapon.ticks_to_aim() as f64 * (1.0 - unit.aim)
-        {
-            Some(Aim {
-                shoot: !intersects_with_friends
-                    && !intersects_with_obstacles
-                    && unit.is_inside_vision(&target.position),
-            })
-        } else {
-            None
-        };
+// the pickup needs the aim at zero, so the unit lowers the weapon once it stands on the better one
+fn is_on_weapon_to_fetch(unit: &Unit) -> bool {
+    weapon_to_fetch(unit)
+        .is_some_and(|l| unit.position.distance(&l.position) < get_constants().unit_radius)
+}
 
-        UnitOrder {
-            target_velocity: (result_move - unit.position.clone()) * 1000.0,
-            target_direction: fire_target - unit.position.clone(),
-            action: action,
-        }
+// the assigned target when the unit may fight it, the closest fightable enemy otherwise
+pub fn fight_target(unit: &Unit) -> Option<&'static Unit> {
+    let targets = fightable_enemies(unit);
+    match get_target(unit.id) {
+        Some(assigned) if targets.iter().any(|e| e.id == assigned.id) => Some(assigned),
+        _ => targets.into_iter().min_by(|a, b| {
+            a.position
+                .distance(&unit.position)
+                .partial_cmp(&b.position.distance(&unit.position))
+                .unwrap()
+        }),
     }
 }
 
@@ -174,6 +175,8 @@ fn get_best_firing_spot(unit: &Unit, target: &&Unit, obstacles: &Vec<Obstacle>)
     let mut best_point = Vec2::default();
     let mut best_score = f64::MIN;
     let constants = get_constants();
+    let best_distance = get_constants().weapons[2].firing_distance() * 0.5;
+    let flank_point = get_team_plan().flank_point(unit.id, target, best_distance);
     for p in unit.points_in_radius(10) {
         if obstacles
             .iter()
@@ -198,11 +201,15 @@ fn get_best_firing_spot(unit: &Unit, target: &&Unit, obstacles: &Vec<Obstacle>)
                     e.position.distance(&p) - get_constants().unit_radius < e.firing_distance()
                         || intersects_with_obstacles_vec(&e.position, &p, obstacles)
                 });
-        let has_obstacles = intersects_with_obstacles_vec(&p, &target.position, obstacles)
-            || intersects_with_units_vec(&p, &target.position, &unit.my_other_units());
+        let has_obstacles = !is_line_of_fire_clear(unit, &p, target, obstacles);
+        // the target shoots back as well
+        let threats = units_in_firing_distance
+            .iter()
+            .copied()
+            .chain([*target])
+            .collect_vec();
 
         let distance_to_target = p.distance(&target.position);
-        let best_distance = get_constants().weapons[2].firing_distance() * 0.5;
         let distance_score = (distance_to_target - best_distance).abs();
 
         // more is better
@@ -213,7 +220,9 @@ fn get_best_firing_spot(unit: &Unit, target: &&Unit, obstacles: &Vec<Obstacle>)
             - my_units_magnet_score(&p, unit)
             + if has_obstacles { -10.0 } else { 10.0 }
             - zone_penalty(&p)
-            - distance_score;
+            - distance_score
+            - p.distance(&flank_point) * FLANK_WEIGHT
+            + cover_score(&p, &threats) * COVER_WEIGHT;
         if best_score < score {
             best_score = score;
             best_point = p;
@@ -0,0 +1,141 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::{BLUE, TRANSPARENT_BLUE};
+use crate::model::{Unit, WeaponProperties};
+use crate::strategy::behaviour::behaviour::{my_units_collision_score, zone_penalty, Behaviour};
+use crate::strategy::behaviour::fighting::{fight_target, Fighting};
+use crate::strategy::behaviour::subtrees::{aim_when, label, lead_target, move_and_act};
+use crate::strategy::behaviour::tree::{Action, Blackboard, Node, Sequence, Status};
+use crate::strategy::holder::{get_constants, get_obstacles};
+use crate::strategy::shots::is_line_of_fire_clear;
+use crate::strategy::util::{bullet_trace_score, get_projectile_traces};
+
+// ranges closer than that are the same for kiting
+const RANGE_MARGIN: f64 = 3.0;
+// distance kept outside the edge of the enemy firing range
+const ENEMY_RANGE_MARGIN: f64 = 1.5;
+// distance kept inside the edge of our firing range, so small steps don't take the enemy out
+const OWN_RANGE_MARGIN: f64 = 1.0;
+// share of our range we close to when the enemy outranges us
+const CLOSE_IN_RATIO: f64 = 0.7;
+const DISTANCE_WEIGHT: f64 = 2.0;
+const BLOCKED_PENALTY: f64 = 5.0;
+// kiting takes over the fight whenever the ranges differ
+const UTILITY_BONUS: f64 = 0.2;
+
+pub struct Kiting {}
+
+impl Behaviour for Kiting {
+    fn name(&self) -> &'static str {
+        "Kiting"
+    }
+
+    fn should_use(&self, unit: &Unit) -> bool {
+        if !(Fighting {}).should_use(unit) {
+            return false;
+        }
+        fight_target(unit).is_some_and(|target| {
+            (unit.firing_distance() - target.firing_distance()).abs() > RANGE_MARGIN
+        })
+    }
+
+    fn utility(&self, unit: &Unit) -> f64 {
+        (Fighting {}).utility(unit) + UTILITY_BONUS
+    }
+
+    fn tree(&'static self) -> Box<dyn Node> {
+        Box::new(Sequence {
+            name: self.name(),
+            children: vec![
+                label(self.name()),
+                lead_target(),
+                move_and_act(
+                    Box::new(Action {
+                        name: "Keep distance",
+                        act: Box::new(keep_distance),
+                    }),
+                    aim_when(Box::new(is_aim_worth_slowing_down)),
+                ),
+            ],
+        })
+    }
+}
+
+// just inside our range, but never inside theirs, or close in when they outrange us
+fn desired_distance(unit: &Unit, target: &Unit) -> f64 {
+    let our_range = unit.firing_distance();
+    let enemy_range = target.firing_distance();
+    if our_range > enemy_range {
+        (our_range - OWN_RANGE_MARGIN)
+            .max(enemy_range + ENEMY_RANGE_MARGIN)
+            .min(our_range)
+    } else {
+        our_range * CLOSE_IN_RATIO
+    }
+}
+
+fn keep_distance(bb: &mut Blackboard, debug_interface: &mut Option<&mut DebugInterface>) -> Status {
+    let unit = bb.unit;
+    let target = match fight_target(unit) {
+        None => return Status::Failure,
+        Some(target) => target,
+    };
+    let obstacles = &get_obstacles(unit.id);
+    let traces = get_projectile_traces();
+    let desired_distance = desired_distance(unit, target);
+
+    let result_move = unit
+        .points_around_unit(true)
+        .into_iter()
+        .map(|p| {
+            let blocked = if is_line_of_fire_clear(unit, &p, target, obstacles) {
+                0.0
+            } else {
+                BLOCKED_PENALTY
+            };
+            let score = bullet_trace_score(&traces, &p)
+                + my_units_collision_score(&p, unit)
+                + zone_penalty(&p)
+                + (p.distance(&target.position) - desired_distance).abs() * DISTANCE_WEIGHT
+                + blocked;
+            (p, score)
+        })
+        .min_by(|e1, e2| f64::total_cmp(&e1.1, &e2.1))
+        .map(|e| e.0)
+        .unwrap_or(unit.position);
+
+    if let Some(debug) = debug_interface.as_mut() {
+        debug.add_ring(target.position, desired_distance, 0.1, TRANSPARENT_BLUE);
+        debug.add_circle(result_move, 0.1, BLUE);
+    }
+    bb.order.target_velocity = (result_move - unit.position) * 1000.0;
+    Status::Success
+}
+
+fn is_aim_worth_slowing_down(bb: &Blackboard) -> bool {
+    let unit = bb.unit;
+    let target = match fight_target(unit) {
+        None => return false,
+        Some(target) => target,
+    };
+    let constants = get_constants();
+    let weapon = &constants.weapons[unit.weapon.unwrap_or(0) as usize];
+    let our_range = unit.firing_distance();
+    let enemy_range = target.firing_distance();
+    let distance = unit.position.distance(&target.position);
+    if our_range > enemy_range {
+        // aiming slows us down, let it go when the enemy would catch up anyway
+        let retreat_speed = aimed_speed(weapon, constants.max_unit_backward_speed);
+        let threatened = distance < enemy_range + ENEMY_RANGE_MARGIN;
+        !threatened || retreat_speed >= constants.max_unit_forward_speed
+    } else {
+        // running in at full speed pays off until the aim is ready by arrival
+        let approach_speed = aimed_speed(weapon, constants.max_unit_forward_speed);
+        let time_to_range = (distance - our_range).max(0.0) / approach_speed;
+        time_to_range <= weapon.aim_time * (1.0 - unit.aim)
+    }
+}
+
+// speed of the unit aiming the whole time
+fn aimed_speed(weapon: &WeaponProperties, speed: f64) -> f64 {
+    speed * weapon.aim_movement_speed_modifier
+}
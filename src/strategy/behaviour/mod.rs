@@ -1,5 +1,11 @@
+pub mod arbiter;
 pub mod behaviour;
 pub mod fighting;
 pub mod ghosting;
+pub mod kiting;
 pub mod move_or_loot;
+pub mod peeking;
 pub mod run_and_heal;
+pub mod scouting;
+pub mod subtrees;
+pub mod tree;
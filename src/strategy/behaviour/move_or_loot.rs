@@ -1,26 +1,52 @@
 use crate::debug_interface::DebugInterface;
-use crate::debugging::{BLUE, GREEN, RED, TRANSPARENT_BLUE, TRANSPARENT_GREEN};
+use crate::debugging::{BLUE, TRANSPARENT_BLUE};
 use crate::model::ActionOrder::Pickup;
-use crate::model::{Loot, Unit, UnitOrder, Vec2};
+use crate::model::{Unit, UnitOrder, Vec2};
 use crate::strategy::behaviour::behaviour::{my_units_collision_score, write_behaviour, Behaviour};
-use crate::strategy::holder::{book_loot, get_constants, get_game, get_loot, remove_loot};
+use crate::strategy::behaviour::tree::{order_tree, Node};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_game, get_loot, get_team_plan, get_zone_plan,
+    remove_loot,
+};
 use crate::strategy::loot::best_loot;
-use crate::strategy::util::{bullet_trace_score, get_projectile_traces, rotate};
+use crate::strategy::sound::sound_to_look_at;
+use crate::strategy::team::Role;
+use crate::strategy::util::{bullet_trace_score, get_projectile_traces};
 
 pub struct MoveOrLoot {}
 
 impl Behaviour for MoveOrLoot {
+    fn name(&self) -> &'static str {
+        "Move"
+    }
+
     fn should_use(&self, unit: &Unit) -> bool {
         true
     }
 
+    fn utility(&self, _unit: &Unit) -> f64 {
+        0.1
+    }
+
+    fn tree(&'static self) -> Box<dyn Node> {
+        order_tree(self.name(), move |unit, debug| self.order(unit, debug))
+    }
+}
+
+impl MoveOrLoot {
     fn order(&self, unit: &Unit, debug_interface: &mut Option<&mut DebugInterface>) -> UnitOrder {
-        write_behaviour(unit, "Move".to_owned(), debug_interface);
+        write_behaviour(unit, self.name().to_owned(), debug_interface);
 
         let game = get_game();
         let constants = get_constants();
         let loot = get_loot();
-        let best_not_intersecting_loot = best_loot(unit, loot, false);
+        let plan = get_team_plan();
+        let covering = plan.role(unit.id) == Role::Cover;
+        let best_not_intersecting_loot = if covering {
+            None
+        } else {
+            best_loot(unit, loot, false)
+        };
         let best_intersecting_loot = best_loot(unit, loot, true);
         let can_pickup = unit.aim == 0.0 && unit.action.is_none();
         if let Some(loot) = &best_intersecting_loot {
@@ -30,39 +56,18 @@ impl Behaviour for MoveOrLoot {
                 }
             }
         }
-        if let Some(ref l) = best_intersecting_loot {
-            book_loot(l.id.clone());
-        }
-        if let Some(ref l) = best_not_intersecting_loot {
-            book_loot(l.id.clone());
-        }
-
         let traces = get_projectile_traces();
 
+        // with nothing to loot the unit either regroups or scouts
         let goal = match best_not_intersecting_loot {
-            None => {
-                let other = unit.my_closest_other_unit();
-                if other.is_some() && other.unwrap().0 > 10.0 {
-                    other.unwrap().1.position
-                } else {
-                    let angle = (get_game()
-                        .my_units()
-                        .iter()
-                        .min_by_key(|e| e.id)
-                        .unwrap()
-                        .position
-                        .clone()
-                        - game.zone.current_center.clone())
-                    .angle();
-                    rotate(
-                        game.zone.current_center.clone(),
-                        angle + 0.1,
-                        game.zone.current_radius * 0.85,
-                    )
-                }
-            }
+            None => plan.regroup_point,
             Some(ref g) => g.position,
         };
+        let goal = if get_zone_plan().is_relocating(unit.id) {
+            get_zone_plan().final_position
+        } else {
+            goal
+        };
 
         let result_move = unit
             .points_around_unit(true)
@@ -83,7 +88,20 @@ impl Behaviour for MoveOrLoot {
             debug.add_circle(result_move.clone(), 0.1, BLUE.clone());
             debug.add_circle(goal.clone(), 1.0, TRANSPARENT_BLUE.clone());
         }
-        let rotation = if get_game().current_tick % 100 >= 85 {
+        let closest_enemy = get_all_enemy_units()
+            .iter()
+            .filter(|e| e.remaining_spawn_time.is_none())
+            .min_by(|a, b| {
+                a.position
+                    .distance(&unit.position)
+                    .partial_cmp(&b.position.distance(&unit.position))
+                    .unwrap()
+            });
+        let rotation = if let (true, Some(enemy)) = (covering, closest_enemy) {
+            enemy.position - unit.position
+        } else if let Some(sound) = sound_to_look_at(unit) {
+            sound
+        } else if get_game().current_tick % 100 >= 85 {
             Vec2 {
                 x: -unit.direction.y,
                 y: unit.direction.x,
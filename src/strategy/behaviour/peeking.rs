@@ -0,0 +1,166 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::TRANSPARENT_GREEN;
+use crate::model::{Obstacle, Unit, Vec2, WeaponProperties};
+use crate::strategy::behaviour::behaviour::Behaviour;
+use crate::strategy::behaviour::fighting::{fight_target, Fighting};
+use crate::strategy::behaviour::subtrees::{
+    aim, hold_aim, label, lead_target, move_and_act, step_to_goal, GOAL,
+};
+use crate::strategy::behaviour::tree::{
+    Action, Blackboard, Condition, Node, Selector, Sequence, Status,
+};
+use crate::strategy::holder::{
+    get_constants, get_cover_map, get_game, get_obstacles, get_peek_cover, set_peek_cover,
+};
+use crate::strategy::shots::is_line_of_fire_clear;
+
+// peek points are up to two unit diameters aside from the cover
+const MAX_PEEK_DISTANCE: f64 = 4.0;
+// peeking is the most specific way to fight, it wins over kiting
+const UTILITY_BONUS: f64 = 0.25;
+
+pub struct Peeking {}
+
+impl Behaviour for Peeking {
+    fn name(&self) -> &'static str {
+        "Peeking"
+    }
+
+    // the target hides behind an obstacle but a side step gives a line of fire
+    fn should_use(&self, unit: &Unit) -> bool {
+        if !(Fighting {}).should_use(unit) {
+            return false;
+        }
+        let target = match fight_target(unit) {
+            None => return false,
+            Some(target) => target,
+        };
+        let cover = cover_spot(unit);
+        let obstacles = &get_obstacles(unit.id);
+        let peek = match peek_spot(unit, &cover, target, obstacles) {
+            None => return false,
+            Some(peek) => peek,
+        };
+        let weapon = &get_constants().weapons[unit.weapon.unwrap_or(0) as usize];
+        peek.distance(&target.position) < unit.firing_distance()
+            && !is_line_of_fire_clear(unit, &cover, target, obstacles)
+            && step_ticks(weapon, &cover, &peek) < return_fire_ticks(target, &peek)
+    }
+
+    fn utility(&self, unit: &Unit) -> f64 {
+        (Fighting {}).utility(unit) + UTILITY_BONUS
+    }
+
+    // the cover is only remembered while peeking out of it
+    fn on_deselect(&self, unit: &Unit) {
+        set_peek_cover(unit.id, None);
+    }
+
+    fn tree(&'static self) -> Box<dyn Node> {
+        Box::new(Sequence {
+            name: self.name(),
+            children: vec![
+                label(self.name()),
+                lead_target(),
+                Box::new(Action {
+                    name: "Cover or peek",
+                    act: Box::new(choose_spot),
+                }),
+                move_and_act(
+                    step_to_goal(true),
+                    Box::new(Selector {
+                        name: "Peek shot",
+                        children: vec![
+                            Box::new(Sequence {
+                                name: "Clear shot",
+                                children: vec![
+                                    Box::new(Condition {
+                                        name: "Line of fire",
+                                        check: Box::new(|bb| {
+                                            fight_target(bb.unit).is_some_and(|target| {
+                                                is_line_of_fire_clear(
+                                                    bb.unit,
+                                                    &bb.unit.position,
+                                                    target,
+                                                    &get_obstacles(bb.unit.id),
+                                                )
+                                            })
+                                        }),
+                                    }),
+                                    aim(),
+                                ],
+                            }),
+                            hold_aim(),
+                        ],
+                    }),
+                ),
+            ],
+        })
+    }
+}
+
+// aim keeps growing behind the cover, we only step out when the shot is ready by arrival
+fn choose_spot(bb: &mut Blackboard, debug_interface: &mut Option<&mut DebugInterface>) -> Status {
+    let unit = bb.unit;
+    let target = match fight_target(unit) {
+        None => return Status::Failure,
+        Some(target) => target,
+    };
+    let weapon = &get_constants().weapons[unit.weapon.unwrap_or(0) as usize];
+    let cover = cover_spot(unit);
+    let peek = peek_spot(unit, &cover, target, &get_obstacles(unit.id)).unwrap_or(cover);
+    set_peek_cover(unit.id, Some(cover));
+
+    let ticks_until_shot = (unit.next_shot_tick - get_game().current_tick).max(0);
+    let ticks_until_aimed = (weapon.ticks_to_aim() as f64 * (1.0 - unit.aim)).ceil() as i32;
+    let ready_in = ticks_until_shot.max(ticks_until_aimed);
+    let goal = if ready_in <= step_ticks(weapon, &cover, &peek) {
+        peek
+    } else {
+        cover
+    };
+
+    if let Some(debug) = debug_interface.as_mut() {
+        debug.add_circle(cover, 0.5, TRANSPARENT_GREEN);
+        debug.add_segment(cover, peek, 0.1, TRANSPARENT_GREEN);
+    }
+    bb.set_point(GOAL, goal);
+    Status::Success
+}
+
+// the spot we came from stays the cover while we peek out of it
+fn cover_spot(unit: &Unit) -> Vec2 {
+    match get_peek_cover(unit.id) {
+        Some(cover) if cover.distance(&unit.position) <= MAX_PEEK_DISTANCE => cover,
+        _ => unit.position,
+    }
+}
+
+// side step out of the cover to a spot the target can be shot from, nothing in the way
+fn peek_spot(unit: &Unit, cover: &Vec2, target: &Unit, obstacles: &[Obstacle]) -> Option<Vec2> {
+    get_cover_map()
+        .peek_point(cover, &target.position)
+        .filter(|peek| is_line_of_fire_clear(unit, peek, target, obstacles))
+}
+
+// ticks to side step between the cover and the peek point while aiming
+fn step_ticks(weapon: &WeaponProperties, cover: &Vec2, peek: &Vec2) -> i32 {
+    let constants = get_constants();
+    let side_speed = (constants.max_unit_forward_speed + constants.max_unit_backward_speed) / 2.0
+        * weapon.aim_movement_speed_modifier
+        / constants.ticks_per_second;
+    (cover.distance(peek) / side_speed.max(f64::EPSILON)).ceil() as i32
+}
+
+// ticks until a shot of the target reaches the point once it sees us there
+fn return_fire_ticks(target: &Unit, p: &Vec2) -> i32 {
+    let constants = get_constants();
+    let weapon = match target.weapon {
+        None => return i32::MAX,
+        Some(w) => &constants.weapons[w as usize],
+    };
+    let aim = (weapon.ticks_to_aim() as f64 * (1.0 - target.aim)).ceil() as i32;
+    let reload = (target.next_shot_tick - get_game().current_tick).max(0);
+    let flight = target.position.distance(p) / weapon.projectile_speed * constants.ticks_per_second;
+    aim.max(reload) + flight.ceil() as i32
+}
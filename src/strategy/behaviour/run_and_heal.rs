@@ -3,77 +3,102 @@ use crate::debugging::{BLUE, GREEN, RED, TRANSPARENT_BLUE};
 use crate::model::ActionOrder::UseShieldPotion;
 use crate::model::{Unit, UnitOrder, Vec2};
 use crate::strategy::behaviour::behaviour::{my_units_magnet_score, write_behaviour, Behaviour};
-use crate::strategy::holder::fight_sim::FightSimResult;
+use crate::strategy::behaviour::tree::{order_tree, Node};
+use crate::strategy::cover::cover_score;
 use crate::strategy::holder::{
     get_all_enemy_units, get_constants, get_fight_simulations, get_game, get_obstacles,
+    get_team_plan,
 };
+use crate::strategy::retreat::plan_retreat;
+use crate::strategy::sound::sound_to_look_at;
+use crate::strategy::sustain::{seconds_until_threat, sustain, Sustain};
 use crate::strategy::util::{
     bullet_trace_score, get_projectile_traces, intersects_with_obstacles, rotate,
 };
 use std::env::set_current_dir;
 
+// seconds of safety are worth that much distance from the enemies
+const THREAT_TIME_WEIGHT: f64 = 2.0;
+
 pub struct RunAndHeal {}
 
 impl Behaviour for RunAndHeal {
+    fn name(&self) -> &'static str {
+        "Run"
+    }
+
     fn should_use(&self, unit: &Unit) -> bool {
         let any_sim_lost = get_fight_simulations().into_iter().any(|s| {
             s.allies.contains(&unit.id)
                 && s.enemy_units()
                     .iter()
                     .any(|e| e.position.distance(&unit.position) <= e.firing_distance())
-                && match s.result {
-                    FightSimResult::WON(_) => false,
-                    FightSimResult::DRAW => false,
-                    FightSimResult::LOST => true,
-                }
+                && !s.is_favourable()
         });
 
-        if any_sim_lost && get_game().current_tick < 5000 {
+        let team_retreats = get_team_plan().retreat_point.is_some();
+
+        if (any_sim_lost || team_retreats) && get_game().current_tick < 5000 {
             return true;
         }
 
-        unit.health < get_constants().unit_health * 0.5
-            || unit.shield < get_constants().max_shield && unit.shield_potions > 0
+        sustain(unit) != Sustain::Nothing
     }
 
-    fn order(&self, unit: &Unit, debug_interface: &mut Option<&mut DebugInterface>) -> UnitOrder {
-        write_behaviour(unit, "Run".to_owned(), debug_interface);
-
-        let mut top_score: f64 = f64::MAX;
-        let mut goal: Vec2 = get_game().zone.current_center.clone();
-        let obstacles = get_obstacles(unit.id);
-        let traces = get_projectile_traces();
-        for p in unit.points_in_radius(10) {
-            if obstacles
+    fn utility(&self, unit: &Unit) -> f64 {
+        let constants = get_constants();
+        let danger = if get_game().current_tick < 5000 {
+            let worst_win_probability = get_fight_simulations()
                 .iter()
-                .find(|o| o.position.distance(&p) < o.radius + get_constants().unit_radius)
-                .is_some()
-            {
-                continue;
-            }
-            if get_game().zone.current_center.distance(&p) + 3.0 >= get_game().zone.current_radius {
-                continue;
-            }
-            if let Some(debug) = debug_interface.as_mut() {
-                debug.add_circle(p.clone(), 0.1, RED.clone());
-            }
-            let enemy_score = get_all_enemy_units()
-                .iter()
-                .map(|e| e.position.distance(&p))
-                .min_by_key(|s| s.ceil() as i64)
-                .unwrap_or(0.0);
-            let distance_from_previous_score = if p.distance(&unit.position) > 3.0 {
-                3.0
+                .filter(|s| s.allies.contains(&unit.id))
+                .filter(|s| {
+                    s.enemy_units()
+                        .iter()
+                        .any(|e| e.position.distance(&unit.position) <= e.firing_distance())
+                })
+                .map(|s| s.result.win_probability)
+                .fold(1.0, f64::min);
+            let team_retreat = if get_team_plan().retreat_point.is_some() {
+                0.7
             } else {
-                p.distance(&unit.position)
+                0.0
             };
-            let res = -enemy_score - distance_from_previous_score
-                + (my_units_magnet_score(&p, unit) / 2.0);
-            if res < top_score {
-                goal = p;
-                top_score = res;
-            }
-        }
+            (1.0 - worst_win_probability).max(team_retreat)
+        } else {
+            0.0
+        };
+        let healing = match sustain(unit) {
+            Sustain::Drinking => 0.9,
+            Sustain::Disengage => 0.8,
+            Sustain::DrinkNow => 0.4,
+            Sustain::WaitForWindow => 0.3,
+            Sustain::Regenerate => 1.0 - unit.health / constants.unit_health,
+            Sustain::Nothing => 0.0,
+        };
+        danger.max(healing)
+    }
+
+    fn tree(&'static self) -> Box<dyn Node> {
+        order_tree(self.name(), move |unit, debug| self.order(unit, debug))
+    }
+}
+
+impl RunAndHeal {
+    fn order(&self, unit: &Unit, debug_interface: &mut Option<&mut DebugInterface>) -> UnitOrder {
+        write_behaviour(unit, self.name().to_owned(), debug_interface);
+
+        let traces = get_projectile_traces();
+        // hide from everybody we know about, the open ground scoring is left for unknown threats
+        let wants_to_drink = unit.shield_potions > 0
+            && matches!(
+                sustain(unit),
+                Sustain::DrinkNow | Sustain::WaitForWindow | Sustain::Disengage
+            );
+        let plan = plan_retreat(unit, wants_to_drink);
+        let goal = match &plan {
+            Some(plan) => plan.next_waypoint(&unit.position),
+            None => open_ground_goal(unit, debug_interface),
+        };
 
         let result_move = unit
             .points_around_unit(true)
@@ -84,7 +109,9 @@ impl Behaviour for RunAndHeal {
             .0
             .clone();
 
-        let rotation = if get_game().current_tick % 100 >= 85 {
+        let rotation = if let Some(sound) = sound_to_look_at(unit) {
+            sound
+        } else if get_game().current_tick % 100 >= 85 {
             Vec2 {
                 x: -unit.direction.y,
                 y: unit.direction.x,
@@ -96,6 +123,10 @@ impl Behaviour for RunAndHeal {
         if let Some(debug) = debug_interface.as_mut() {
             debug.add_circle(result_move.clone(), 0.1, BLUE.clone());
             debug.add_circle(goal.clone(), 1.0, RED.clone());
+            if let Some(plan) = &plan {
+                debug.add_poly_line(plan.path.clone(), 0.1, TRANSPARENT_BLUE);
+                debug.add_circle(plan.destination, 0.5, GREEN);
+            }
             for x in get_projectile_traces() {
                 debug.add_circle(x.position, 0.1, BLUE.clone());
             }
@@ -103,7 +134,57 @@ impl Behaviour for RunAndHeal {
         UnitOrder {
             target_velocity: (result_move - unit.position.clone()) * 1000.0,
             target_direction: rotation,
-            action: Some(UseShieldPotion {}),
+            action: match sustain(unit) {
+                Sustain::Drinking | Sustain::DrinkNow => Some(UseShieldPotion {}),
+                _ => None,
+            },
+        }
+    }
+}
+
+// spot away from the enemies, towards the teammates and the team retreat point
+fn open_ground_goal(unit: &Unit, debug_interface: &mut Option<&mut DebugInterface>) -> Vec2 {
+    let mut top_score: f64 = f64::MAX;
+    let mut goal: Vec2 = get_game().zone.current_center.clone();
+    let obstacles = get_obstacles(unit.id);
+    let enemies = get_all_enemy_units().iter().collect::<Vec<_>>();
+    for p in unit.points_in_radius(10) {
+        if obstacles
+            .iter()
+            .find(|o| o.position.distance(&p) < o.radius + get_constants().unit_radius)
+            .is_some()
+        {
+            continue;
+        }
+        if get_game().zone.current_center.distance(&p) + 3.0 >= get_game().zone.current_radius {
+            continue;
+        }
+        if let Some(debug) = debug_interface.as_mut() {
+            debug.add_circle(p.clone(), 0.1, RED.clone());
+        }
+        let enemy_score = get_all_enemy_units()
+            .iter()
+            .map(|e| e.position.distance(&p))
+            .min_by_key(|s| s.ceil() as i64)
+            .unwrap_or(0.0);
+        let distance_from_previous_score = if p.distance(&unit.position) > 3.0 {
+            3.0
+        } else {
+            p.distance(&unit.position)
+        };
+        let retreat_score = get_team_plan()
+            .retreat_point
+            .map(|r| r.distance(&p) * 0.5)
+            .unwrap_or(0.0);
+        let res = -enemy_score - distance_from_previous_score
+            + (my_units_magnet_score(&p, unit) / 2.0)
+            + retreat_score
+            - cover_score(&p, &enemies) * 3.0
+            - seconds_until_threat(&p) * THREAT_TIME_WEIGHT;
+        if res < top_score {
+            goal = p;
+            top_score = res;
         }
     }
+    goal
 }
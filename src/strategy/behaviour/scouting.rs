@@ -0,0 +1,105 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::{BLUE, TRANSPARENT_TEAL};
+use crate::model::ActionOrder::Pickup;
+use crate::model::{Unit, UnitOrder, Vec2};
+use crate::strategy::behaviour::behaviour::{my_units_collision_score, write_behaviour, Behaviour};
+use crate::strategy::behaviour::tree::{order_tree, Node};
+use crate::strategy::exploration::scouting_target;
+use crate::strategy::holder::{
+    get_game, get_loot, get_scouting_target, get_team_plan, get_zone_plan, remove_loot,
+    set_scouting_target,
+};
+use crate::strategy::loot::best_loot;
+use crate::strategy::sound::sound_to_look_at;
+use crate::strategy::team::Role;
+use crate::strategy::util::{bullet_trace_score, get_projectile_traces};
+use crate::strategy::zone::orbit_point;
+
+// the team scouts together, units further from each other regroup first
+const MAX_ALLY_DISTANCE: f64 = 10.0;
+
+pub struct Scouting {}
+
+impl Behaviour for Scouting {
+    fn name(&self) -> &'static str {
+        "Scouting"
+    }
+
+    // nothing else to do: no loot to go for, nobody to cover and the zone is not a problem
+    fn should_use(&self, unit: &Unit) -> bool {
+        unit.remaining_spawn_time.is_none()
+            && get_team_plan().role(unit.id) != Role::Cover
+            && !get_zone_plan().is_relocating(unit.id)
+            && best_loot(unit, get_loot(), false).is_none()
+            && unit
+                .my_closest_other_unit()
+                .is_none_or(|(distance, _)| distance <= MAX_ALLY_DISTANCE)
+    }
+
+    fn utility(&self, _unit: &Unit) -> f64 {
+        0.15
+    }
+
+    fn tree(&'static self) -> Box<dyn Node> {
+        order_tree(self.name(), move |unit, debug| self.order(unit, debug))
+    }
+}
+
+impl Scouting {
+    fn order(&self, unit: &Unit, debug_interface: &mut Option<&mut DebugInterface>) -> UnitOrder {
+        write_behaviour(unit, self.name().to_owned(), debug_interface);
+
+        // everybody heads to the same cell so the team stays together
+        let leader = get_team_plan().regroup_point;
+        let goal = match scouting_target(&leader, get_scouting_target()) {
+            Some(target) => {
+                set_scouting_target(Some(target));
+                target
+            }
+            None => orbit_point(&leader),
+        };
+
+        let traces = get_projectile_traces();
+        let result_move = unit
+            .points_around_unit(true)
+            .into_iter()
+            .map(|p| {
+                let score = bullet_trace_score(&traces, &p)
+                    + my_units_collision_score(&p, unit)
+                    + p.distance(&goal);
+                (p, score)
+            })
+            .min_by(|e1, e2| f64::partial_cmp(&e1.1, &e2.1).unwrap())
+            .map(|e| e.0)
+            .unwrap_or(goal);
+        if let Some(debug) = debug_interface.as_mut() {
+            debug.add_circle(result_move, 0.1, BLUE);
+            debug.add_circle(goal, 1.0, TRANSPARENT_TEAL);
+        }
+
+        let rotation = if let Some(sound) = sound_to_look_at(unit) {
+            sound
+        } else if get_game().current_tick % 100 >= 85 {
+            Vec2 {
+                x: -unit.direction.y,
+                y: unit.direction.x,
+            }
+        } else {
+            goal - unit.position
+        };
+
+        let can_pickup = unit.aim == 0.0 && unit.action.is_none();
+        let pickup_action = match best_loot(unit, get_loot(), true) {
+            Some(loot) if can_pickup => {
+                unsafe { remove_loot(loot.id) };
+                Some(Pickup { loot: loot.id })
+            }
+            _ => None,
+        };
+        UnitOrder {
+            target_velocity: (result_move - unit.position) * 1000.0,
+            target_direction: rotation,
+            action: pickup_action,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::debugging::{BLUE, RED, TRANSPARENT_BLUE};
+use crate::model::ActionOrder::Aim;
+use crate::strategy::behaviour::behaviour::{
+    my_units_collision_score, write_behaviour, zone_penalty,
+};
+use crate::strategy::behaviour::fighting::fight_target;
+use crate::strategy::behaviour::tree::{
+    Action, Blackboard, Condition, ConditionFn, Decorator, DecoratorKind, Node, Parallel, Selector,
+    Sequence, SetPoint, Status,
+};
+use crate::strategy::holder::{get_constants, get_obstacles};
+use crate::strategy::shots::is_worth_shooting;
+use crate::strategy::util::{bullet_trace_score, get_projectile_traces};
+
+// blackboard points shared by the subtrees
+pub const FIRE_TARGET: &str = "fire target";
+pub const GOAL: &str = "goal";
+
+// behaviour name over the unit
+pub fn label(name: &'static str) -> Box<dyn Node> {
+    Box::new(Action {
+        name: "Label",
+        act: Box::new(move |bb, debug| {
+            write_behaviour(bb.unit, name.to_owned(), debug);
+            Status::Success
+        }),
+    })
+}
+
+// where the projectile meets the fight target, the unit faces it
+pub fn lead_target() -> Box<dyn Node> {
+    Box::new(Sequence {
+        name: "Lead target",
+        children: vec![
+            Box::new(SetPoint {
+                name: "Fire target",
+                key: FIRE_TARGET,
+                compute: Box::new(|bb| {
+                    let target = fight_target(bb.unit)?;
+                    let weapon = &get_constants().weapons[bb.unit.weapon.unwrap_or(0) as usize];
+                    let distance = bb.unit.position.distance(&target.position);
+                    Some(target.position + target.velocity * distance / weapon.projectile_speed)
+                }),
+            }),
+            Box::new(Action {
+                name: "Face",
+                act: Box::new(|bb, debug| {
+                    let fire_target = bb.point(FIRE_TARGET).unwrap();
+                    bb.order.target_direction = fire_target - bb.unit.position;
+                    if let (Some(debug), Some(target)) = (debug.as_mut(), fight_target(bb.unit)) {
+                        debug.add_circle(target.position, 0.5, RED);
+                    }
+                    Status::Success
+                }),
+            }),
+        ],
+    })
+}
+
+// Step towards the goal point. Dodging units keep out of the projectiles, ghosts can't be hit
+// and only keep away from the zone edge.
+pub fn step_to_goal(dodge: bool) -> Box<dyn Node> {
+    Box::new(Action {
+        name: "Step to goal",
+        act: Box::new(move |bb, debug| {
+            let goal = match bb.point(GOAL) {
+                None => return Status::Failure,
+                Some(goal) => goal,
+            };
+            let unit = bb.unit;
+            let traces = get_projectile_traces();
+            let result_move = unit
+                .points_around_unit(dodge)
+                .into_iter()
+                .map(|p| {
+                    let danger = if dodge {
+                        bullet_trace_score(&traces, &p)
+                    } else {
+                        zone_penalty(&p)
+                    };
+                    let score = danger + my_units_collision_score(&p, unit) + p.distance(&goal);
+                    (p, score)
+                })
+                .min_by(|e1, e2| f64::total_cmp(&e1.1, &e2.1))
+                .map(|e| e.0)
+                .unwrap_or(goal);
+            if let Some(debug) = debug.as_mut() {
+                debug.add_circle(result_move, 0.1, BLUE);
+                debug.add_circle(goal, 1.0, TRANSPARENT_BLUE);
+            }
+            bb.order.target_velocity = (result_move - unit.position) * 1000.0;
+            Status::Success
+        }),
+    })
+}
+
+// aims at the fire target, shoots when the shot is worth it
+pub fn aim() -> Box<dyn Node> {
+    Box::new(Selector {
+        name: "Aim",
+        children: vec![
+            Box::new(Sequence {
+                name: "Shoot",
+                children: vec![
+                    Box::new(Condition {
+                        name: "Worth shooting",
+                        check: Box::new(is_shot_worth_it),
+                    }),
+                    aim_action("Fire", true),
+                ],
+            }),
+            hold_aim(),
+        ],
+    })
+}
+
+// aim keeps growing without wasting the shot
+pub fn hold_aim() -> Box<dyn Node> {
+    aim_action("Hold aim", false)
+}
+
+// aims when the behaviour is ready to, the unit keeps going either way
+pub fn aim_when(ready: ConditionFn) -> Box<dyn Node> {
+    Box::new(Decorator {
+        name: "Maybe aim",
+        kind: DecoratorKind::Succeeder,
+        child: Box::new(Sequence {
+            name: "Aim when ready",
+            children: vec![
+                Box::new(Condition {
+                    name: "Ready to aim",
+                    check: ready,
+                }),
+                aim(),
+            ],
+        }),
+    })
+}
+
+// moving and acting don't depend on each other, both run every tick
+pub fn move_and_act(step: Box<dyn Node>, act: Box<dyn Node>) -> Box<dyn Node> {
+    Box::new(Parallel {
+        name: "Move and act",
+        children: vec![step, act],
+        required_successes: 2,
+    })
+}
+
+fn aim_action(name: &'static str, shoot: bool) -> Box<dyn Node> {
+    Box::new(Action {
+        name,
+        act: Box::new(move |bb, _debug| {
+            bb.order.action = Some(Aim { shoot });
+            Status::Success
+        }),
+    })
+}
+
+fn is_shot_worth_it(bb: &Blackboard) -> bool {
+    let unit = bb.unit;
+    let (target, fire_target) = match (fight_target(unit), bb.point(FIRE_TARGET)) {
+        (Some(target), Some(fire_target)) => (target, fire_target),
+        _ => return false,
+    };
+    unit.position.distance(&target.position) < unit.firing_distance()
+        && unit.is_inside_vision(&target.position)
+        && is_worth_shooting(unit, target, &fire_target, &get_obstacles(unit.id))
+}
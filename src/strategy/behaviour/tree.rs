@@ -0,0 +1,294 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::TEAL;
+use crate::model::{Unit, UnitOrder, Vec2};
+use crate::strategy::behaviour::arbiter::choose_behaviour;
+use crate::strategy::behaviour::behaviour::Behaviour;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+pub type ConditionFn = Box<dyn Fn(&Blackboard) -> bool>;
+pub type ActionFn = Box<dyn Fn(&mut Blackboard, &mut Option<&mut DebugInterface>) -> Status>;
+pub type PointFn = Box<dyn Fn(&Blackboard) -> Option<Vec2>>;
+
+// state shared by all the nodes while a unit's tree is ticked
+pub struct Blackboard<'a> {
+    pub unit: &'a Unit,
+    pub order: UnitOrder,
+    points: HashMap<&'static str, Vec2>,
+    // nodes that did not fail this tick together with their depth
+    path: Vec<(usize, &'static str)>,
+    depth: usize,
+}
+
+impl<'a> Blackboard<'a> {
+    pub fn new(unit: &'a Unit) -> Self {
+        Blackboard {
+            unit,
+            order: UnitOrder {
+                target_velocity: Default::default(),
+                target_direction: Default::default(),
+                action: None,
+            },
+            points: HashMap::new(),
+            path: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    pub fn point(&self, key: &str) -> Option<Vec2> {
+        self.points.get(key).copied()
+    }
+
+    pub fn set_point(&mut self, key: &'static str, p: Vec2) {
+        self.points.insert(key, p);
+    }
+
+    pub fn draw_active_path(&self, debug_interface: &mut Option<&mut DebugInterface>) {
+        if let Some(debug) = debug_interface.as_mut() {
+            for (i, (depth, name)) in self.path.iter().enumerate() {
+                debug.add_placed_text(
+                    self.unit.position
+                        + Vec2 {
+                            x: *depth as f64,
+                            y: -2.0 - i as f64,
+                        },
+                    name.to_string(),
+                    Vec2 { x: 0.0, y: 0.0 },
+                    0.7,
+                    TEAL,
+                );
+            }
+        }
+    }
+}
+
+pub trait Node {
+    fn name(&self) -> &'static str;
+    fn tick(
+        &self,
+        bb: &mut Blackboard,
+        debug_interface: &mut Option<&mut DebugInterface>,
+    ) -> Status;
+}
+
+// ticks the node and keeps it in the active path unless it failed
+pub fn run(
+    node: &dyn Node,
+    bb: &mut Blackboard,
+    debug_interface: &mut Option<&mut DebugInterface>,
+) -> Status {
+    let path_len = bb.path.len();
+    bb.path.push((bb.depth, node.name()));
+    bb.depth += 1;
+    let status = node.tick(bb, debug_interface);
+    bb.depth -= 1;
+    if status == Status::Failure {
+        bb.path.truncate(path_len);
+    }
+    status
+}
+
+// first child that doesn't fail
+pub struct Selector {
+    pub name: &'static str,
+    pub children: Vec<Box<dyn Node>>,
+}
+
+impl Node for Selector {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, debug: &mut Option<&mut DebugInterface>) -> Status {
+        for child in &self.children {
+            let status = run(child.as_ref(), bb, debug);
+            if status != Status::Failure {
+                return status;
+            }
+        }
+        Status::Failure
+    }
+}
+
+// children one by one until one of them doesn't succeed
+pub struct Sequence {
+    pub name: &'static str,
+    pub children: Vec<Box<dyn Node>>,
+}
+
+impl Node for Sequence {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, debug: &mut Option<&mut DebugInterface>) -> Status {
+        for child in &self.children {
+            let status = run(child.as_ref(), bb, debug);
+            if status != Status::Success {
+                return status;
+            }
+        }
+        Status::Success
+    }
+}
+
+// all children every tick, succeeds once enough of them succeeded
+pub struct Parallel {
+    pub name: &'static str,
+    pub children: Vec<Box<dyn Node>>,
+    pub required_successes: usize,
+}
+
+impl Node for Parallel {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, debug: &mut Option<&mut DebugInterface>) -> Status {
+        let mut successes = 0;
+        let mut failures = 0;
+        for child in &self.children {
+            match run(child.as_ref(), bb, debug) {
+                Status::Success => successes += 1,
+                Status::Failure => failures += 1,
+                Status::Running => {}
+            }
+        }
+        if successes >= self.required_successes {
+            Status::Success
+        } else if self.children.len() - failures < self.required_successes {
+            Status::Failure
+        } else {
+            Status::Running
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum DecoratorKind {
+    Inverter,
+    Succeeder,
+}
+
+pub struct Decorator {
+    pub name: &'static str,
+    pub kind: DecoratorKind,
+    pub child: Box<dyn Node>,
+}
+
+impl Node for Decorator {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, debug: &mut Option<&mut DebugInterface>) -> Status {
+        let status = run(self.child.as_ref(), bb, debug);
+        match (self.kind, status) {
+            (DecoratorKind::Inverter, Status::Success) => Status::Failure,
+            (DecoratorKind::Inverter, Status::Failure) => Status::Success,
+            (DecoratorKind::Succeeder, _) => Status::Success,
+            (_, status) => status,
+        }
+    }
+}
+
+pub struct Condition {
+    pub name: &'static str,
+    pub check: ConditionFn,
+}
+
+impl Node for Condition {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, _debug: &mut Option<&mut DebugInterface>) -> Status {
+        if (self.check)(bb) {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+pub struct Action {
+    pub name: &'static str,
+    pub act: ActionFn,
+}
+
+impl Node for Action {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, debug: &mut Option<&mut DebugInterface>) -> Status {
+        (self.act)(bb, debug)
+    }
+}
+
+// writes a point to the blackboard, fails if there is nothing to write
+pub struct SetPoint {
+    pub name: &'static str,
+    pub key: &'static str,
+    pub compute: PointFn,
+}
+
+impl Node for SetPoint {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, _debug: &mut Option<&mut DebugInterface>) -> Status {
+        match (self.compute)(bb) {
+            None => Status::Failure,
+            Some(p) => {
+                bb.set_point(self.key, p);
+                Status::Success
+            }
+        }
+    }
+}
+
+// a behaviour without inner structure, one action gives the whole order
+pub fn order_tree(
+    name: &'static str,
+    order: impl Fn(&Unit, &mut Option<&mut DebugInterface>) -> UnitOrder + 'static,
+) -> Box<dyn Node> {
+    Box::new(Action {
+        name,
+        act: Box::new(move |bb, debug| {
+            bb.order = order(bb.unit, debug);
+            Status::Success
+        }),
+    })
+}
+
+// selector which orders children by the behaviour utility instead of their position
+pub struct UtilitySelector {
+    pub name: &'static str,
+    pub options: Vec<(&'static dyn Behaviour, Box<dyn Node>)>,
+}
+
+impl Node for UtilitySelector {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn tick(&self, bb: &mut Blackboard, debug: &mut Option<&mut DebugInterface>) -> Status {
+        let behaviours = self.options.iter().map(|(b, _)| *b).collect::<Vec<_>>();
+        let chosen = match choose_behaviour(bb.unit, &behaviours) {
+            None => return Status::Failure,
+            Some(chosen) => chosen.name(),
+        };
+        match self.options.iter().find(|(b, _)| b.name() == chosen) {
+            None => Status::Failure,
+            Some((_, subtree)) => run(subtree.as_ref(), bb, debug),
+        }
+    }
+}
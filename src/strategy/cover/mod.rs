@@ -0,0 +1,220 @@
+use crate::model::{Obstacle, Unit, Vec2};
+use crate::strategy::holder::{get_constants, get_cover_map};
+use std::f64::consts::PI;
+
+const CELL_SIZE: f64 = 1.0;
+const DIRECTIONS: usize = 16;
+// obstacles further than this don't protect from the threat in practice
+const COVER_RANGE: f64 = 8.0;
+// center ray and two rays along the unit's edges
+const RAYS: usize = 3;
+const CENTER_RAY: u8 = 1 << 1;
+
+// For every cell and every threat direction keeps which of the rays from the unit's
+// center and edges towards the threat are stopped by a non-shoot-through obstacle.
+#[derive(Clone, Debug)]
+pub struct CoverMap {
+    origin: Vec2,
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+}
+
+impl CoverMap {
+    pub const fn const_default() -> Self {
+        CoverMap {
+            origin: Vec2 { x: 0.0, y: 0.0 },
+            width: 0,
+            height: 0,
+            cells: vec![],
+        }
+    }
+
+    // 0 - fully exposed, 1 - fully shielded from the threat
+    pub fn cover(&self, p: &Vec2, threat: &Vec2) -> f64 {
+        self.blocked_rays(p, threat).count_ones() as f64 / RAYS as f64
+    }
+
+    // shielded at p, but a side step is enough to fire at the threat
+    pub fn can_peek(&self, p: &Vec2, threat: &Vec2) -> bool {
+        self.cover(p, threat) > 0.0 && self.peek_point(p, threat).is_some()
+    }
+
+    pub fn peek_point(&self, p: &Vec2, threat: &Vec2) -> Option<Vec2> {
+        let direction = (*threat - *p) / p.distance(threat).max(f64::EPSILON);
+        let side = Vec2 {
+            x: -direction.y,
+            y: direction.x,
+        };
+        let constants = get_constants();
+        let step = constants.unit_radius * 2.0;
+        [
+            side * step,
+            side * -step,
+            side * step * 2.0,
+            side * -step * 2.0,
+        ]
+        .into_iter()
+        .map(|offset| *p + offset)
+        // the unit has to fit there
+        .filter(|peek| {
+            constants
+                .obstacles
+                .iter()
+                .all(|o| o.position.distance(peek) >= o.radius + constants.unit_radius)
+        })
+        .find(|peek| self.blocked_rays(peek, threat) & CENTER_RAY == 0)
+    }
+
+    fn blocked_rays(&self, p: &Vec2, threat: &Vec2) -> u8 {
+        match self.cell_index(p) {
+            None => 0,
+            Some(cell) => self.cells[cell * DIRECTIONS + direction_index(p, threat)],
+        }
+    }
+
+    fn cell_index(&self, p: &Vec2) -> Option<usize> {
+        let x = ((p.x - self.origin.x) / CELL_SIZE).floor();
+        let y = ((p.y - self.origin.y) / CELL_SIZE).floor();
+        if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+            return None;
+        }
+        Some(y as usize * self.width + x as usize)
+    }
+
+    fn cell_center(&self, x: usize, y: usize) -> Vec2 {
+        Vec2 {
+            x: self.origin.x + (x as f64 + 0.5) * CELL_SIZE,
+            y: self.origin.y + (y as f64 + 0.5) * CELL_SIZE,
+        }
+    }
+}
+
+fn direction_index(p: &Vec2, threat: &Vec2) -> usize {
+    let angle = (*threat - *p).angle();
+    let angle = if angle.is_nan() { 0.0 } else { angle };
+    let sector = 2.0 * PI / DIRECTIONS as f64;
+    (((angle + 2.0 * PI) / sector).round() as usize) % DIRECTIONS
+}
+
+// built once from the static obstacles, only cells near obstacles can have cover
+pub fn build_cover_map() -> CoverMap {
+    let constants = get_constants();
+    let blocking = constants
+        .obstacles
+        .iter()
+        .filter(|o| !o.can_shoot_through)
+        .collect::<Vec<_>>();
+    if blocking.is_empty() {
+        return CoverMap::const_default();
+    }
+    let margin = COVER_RANGE + constants.unit_radius + CELL_SIZE;
+    let min_x = blocking
+        .iter()
+        .map(|o| o.position.x - o.radius)
+        .fold(f64::MAX, f64::min)
+        - margin;
+    let min_y = blocking
+        .iter()
+        .map(|o| o.position.y - o.radius)
+        .fold(f64::MAX, f64::min)
+        - margin;
+    let max_x = blocking
+        .iter()
+        .map(|o| o.position.x + o.radius)
+        .fold(f64::MIN, f64::max)
+        + margin;
+    let max_y = blocking
+        .iter()
+        .map(|o| o.position.y + o.radius)
+        .fold(f64::MIN, f64::max)
+        + margin;
+    let width = ((max_x - min_x) / CELL_SIZE).ceil() as usize;
+    let height = ((max_y - min_y) / CELL_SIZE).ceil() as usize;
+    let mut map = CoverMap {
+        origin: Vec2 { x: min_x, y: min_y },
+        width,
+        height,
+        cells: vec![0; width * height * DIRECTIONS],
+    };
+
+    let directions = (0..DIRECTIONS)
+        .map(|d| {
+            let angle = 2.0 * PI * d as f64 / DIRECTIONS as f64;
+            Vec2 {
+                x: angle.cos(),
+                y: angle.sin(),
+            }
+        })
+        .collect::<Vec<_>>();
+    for obstacle in blocking {
+        let reach = obstacle.radius + COVER_RANGE + constants.unit_radius;
+        let from = map.cell_index(&(obstacle.position - Vec2 { x: reach, y: reach }));
+        let to = map.cell_index(&(obstacle.position + Vec2 { x: reach, y: reach }));
+        let (from, to) = match (from, to) {
+            (Some(from), Some(to)) => (from, to),
+            _ => continue,
+        };
+        for y in from / width..=to / width {
+            for x in from % width..=to % width {
+                let center = map.cell_center(x, y);
+                let cell = y * width + x;
+                for (d, direction) in directions.iter().enumerate() {
+                    map.cells[cell * DIRECTIONS + d] |=
+                        blocked_rays_mask(&center, direction, obstacle, constants.unit_radius);
+                }
+            }
+        }
+    }
+    map
+}
+
+fn blocked_rays_mask(center: &Vec2, direction: &Vec2, obstacle: &Obstacle, unit_radius: f64) -> u8 {
+    let side = Vec2 {
+        x: -direction.y,
+        y: direction.x,
+    };
+    let mut mask = 0;
+    for ray in 0..RAYS {
+        let origin = *center + side * (unit_radius * (ray as f64 - 1.0));
+        let to_obstacle = obstacle.position - origin;
+        let along = to_obstacle.x * direction.x + to_obstacle.y * direction.y;
+        let across = (to_obstacle.x * direction.y - to_obstacle.y * direction.x).abs();
+        if along > 0.0 && along < COVER_RANGE + obstacle.radius && across < obstacle.radius {
+            mask |= 1 << ray;
+        }
+    }
+    mask
+}
+
+// average cover against every enemy which can shoot at the point
+pub fn cover_score(p: &Vec2, enemies: &[&Unit]) -> f64 {
+    let threats = enemies
+        .iter()
+        .filter(|e| e.position.distance(p) < e.firing_distance() + get_constants().unit_radius)
+        .collect::<Vec<_>>();
+    if threats.is_empty() {
+        return 0.0;
+    }
+    threats
+        .iter()
+        .map(|e| get_cover_map().cover(p, &e.position))
+        .sum::<f64>()
+        / threats.len() as f64
+}
+
+// cover against a threat coming from any direction
+pub fn all_around_cover(p: &Vec2) -> f64 {
+    (0..DIRECTIONS)
+        .map(|d| {
+            let angle = 2.0 * PI * d as f64 / DIRECTIONS as f64;
+            let threat = *p
+                + Vec2 {
+                    x: angle.cos(),
+                    y: angle.sin(),
+                };
+            get_cover_map().cover(p, &threat)
+        })
+        .sum::<f64>()
+        / DIRECTIONS as f64
+}
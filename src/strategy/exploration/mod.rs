@@ -0,0 +1,112 @@
+use crate::model::{Game, Vec2};
+use crate::strategy::holder::{
+    get_constants, get_enemy_beliefs, get_exploration_grid, get_game, inside_vision,
+};
+use crate::strategy::zone::{predicted_zone, ZONE_LOOKAHEAD_SECONDS};
+
+const CELL_SIZE: f64 = 5.0;
+// cells not seen for that long are as stale as it gets
+const STALE_TICKS: f64 = 600.0;
+const SAFE_ZONE_RATIO: f64 = 0.85;
+const LOST_ENEMY_WEIGHT: f64 = 0.5;
+const DISTANCE_WEIGHT: f64 = 0.3;
+const STABILITY_BONUS: f64 = 0.2;
+
+// Coarse grid over the starting zone keeping the last tick every cell was inside
+// the vision of any of our units.
+#[derive(Clone, Debug)]
+pub struct ExplorationGrid {
+    origin: Vec2,
+    size: usize,
+    last_seen: Vec<i32>,
+}
+
+impl ExplorationGrid {
+    pub const fn const_default() -> Self {
+        ExplorationGrid {
+            origin: Vec2 { x: 0.0, y: 0.0 },
+            size: 0,
+            last_seen: vec![],
+        }
+    }
+
+    // the zone only shrinks, so the first one covers the whole game
+    fn new(game: &Game) -> Self {
+        let radius = game.zone.current_radius;
+        let size = (2.0 * radius / CELL_SIZE).ceil() as usize;
+        ExplorationGrid {
+            origin: game.zone.current_center
+                - Vec2 {
+                    x: radius,
+                    y: radius,
+                },
+            size,
+            last_seen: vec![0; size * size],
+        }
+    }
+
+    pub fn update(&mut self, game: &Game) {
+        if self.size == 0 {
+            *self = ExplorationGrid::new(game);
+        }
+        let zone = &game.zone;
+        for i in 0..self.last_seen.len() {
+            let center = self.cell_center(i);
+            if center.distance(&zone.current_center) > zone.current_radius + CELL_SIZE {
+                continue;
+            }
+            if inside_vision(game, &center) {
+                self.last_seen[i] = game.current_tick;
+            }
+        }
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (Vec2, i32)> + '_ {
+        (0..self.last_seen.len()).map(move |i| (self.cell_center(i), self.last_seen[i]))
+    }
+
+    pub fn last_seen(&self, p: &Vec2) -> Option<i32> {
+        let x = ((p.x - self.origin.x) / CELL_SIZE).floor();
+        let y = ((p.y - self.origin.y) / CELL_SIZE).floor();
+        if x < 0.0 || y < 0.0 || x >= self.size as f64 || y >= self.size as f64 {
+            return None;
+        }
+        Some(self.last_seen[y as usize * self.size + x as usize])
+    }
+
+    fn cell_center(&self, i: usize) -> Vec2 {
+        Vec2 {
+            x: self.origin.x + ((i % self.size) as f64 + 0.5) * CELL_SIZE,
+            y: self.origin.y + ((i / self.size) as f64 + 0.5) * CELL_SIZE,
+        }
+    }
+}
+
+// Cell worth looking at: not seen for long, likely to hide loot or enemies we lost,
+// not too far and safe from the zone. Keeps the previous target while it is still stale.
+pub fn scouting_target(from: &Vec2, previous: Option<Vec2>) -> Option<Vec2> {
+    let constants = get_constants();
+    let tick = get_game().current_tick;
+    let (center, radius) = predicted_zone(ZONE_LOOKAHEAD_SECONDS);
+    get_exploration_grid()
+        .cells()
+        .filter(|(p, _)| p.distance(&center) < radius * SAFE_ZONE_RATIO)
+        .map(|(p, last_seen)| {
+            let staleness = ((tick - last_seen) as f64 / STALE_TICKS).min(1.0);
+            let lost_enemies = get_enemy_beliefs()
+                .iter()
+                .filter(|b| b.last_seen_tick != tick)
+                .filter(|b| b.unit.position.distance(&p) < constants.view_distance / 2.0)
+                .count() as f64;
+            let stability = match previous {
+                Some(previous) if previous.distance(&p) < CELL_SIZE => STABILITY_BONUS,
+                _ => 0.0,
+            };
+            let score = staleness * (1.0 + lost_enemies * LOST_ENEMY_WEIGHT) + stability
+                - p.distance(from) / constants.view_distance * DISTANCE_WEIGHT;
+            (p, staleness, score)
+        })
+        .filter(|(_, staleness, _)| *staleness > 0.0)
+        .max_by(|(_, _, s1), (_, _, s2)| s1.partial_cmp(s2).unwrap())
+        .map(|(p, _, _)| p)
+}
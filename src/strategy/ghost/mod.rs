@@ -0,0 +1,144 @@
+use crate::model::{Unit, Vec2};
+use crate::strategy::cover::all_around_cover;
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_exploration_grid, get_game, get_loot,
+};
+use crate::strategy::util::rotate;
+use crate::strategy::zone::predicted_zone;
+use std::f64::consts::PI;
+
+const DIRECTIONS: i32 = 12;
+const RINGS: i32 = 3;
+// points sampled around an exploration candidate to estimate what it reveals
+const REVEAL_SAMPLES: i32 = 8;
+// time kept in reserve to get back to the materialisation spot
+const RETURN_MARGIN_SECONDS: f64 = 0.5;
+// distance inside the zone we want to materialise at
+const ZONE_MARGIN: f64 = 5.0;
+const ENEMY_WEIGHT: f64 = 1.0;
+// the zone is too small to come back once more, dying now is final
+const LAST_LIFE_ENEMY_WEIGHT: f64 = 3.0;
+const COVER_WEIGHT: f64 = 3.0;
+const LOOT_WEIGHT: f64 = 1.0;
+// coming back next to a teammate beats coming back alone
+const ALLY_WEIGHT: f64 = 0.1;
+
+// where the ghost should move right now: explore while there is time, then materialise
+pub fn ghost_goal(unit: &Unit) -> Vec2 {
+    let spot = materialisation_spot(unit);
+    let time_left = unit.remaining_spawn_time.unwrap_or(0.0);
+    let speed = get_constants().spawn_movement_speed.max(f64::EPSILON);
+    let spare = time_left - unit.position.distance(&spot) / speed - RETURN_MARGIN_SECONDS;
+    if spare <= 0.0 {
+        return spot;
+    }
+    exploration_point(unit, &spot, time_left, speed).unwrap_or(spot)
+}
+
+// point revealing most of what the team hasn't seen for long, from which we still make it to the spot
+fn exploration_point(unit: &Unit, spot: &Vec2, time_left: f64, speed: f64) -> Option<Vec2> {
+    let view_distance = get_constants().view_distance;
+    candidates(unit, time_left * speed / 2.0)
+        .into_iter()
+        .filter(|p| {
+            (unit.position.distance(p) + p.distance(spot)) / speed + RETURN_MARGIN_SECONDS
+                < time_left
+        })
+        .map(|p| {
+            let hidden = (0..REVEAL_SAMPLES)
+                .map(|i| {
+                    rotate(
+                        p,
+                        2.0 * PI * i as f64 / REVEAL_SAMPLES as f64,
+                        view_distance * 0.6,
+                    )
+                })
+                .filter_map(|s| get_exploration_grid().last_seen(&s))
+                .map(|last_seen| get_game().current_tick - last_seen)
+                .sum::<i32>();
+            (p, hidden)
+        })
+        .filter(|(_, hidden)| *hidden > 0)
+        .max_by_key(|(_, hidden)| *hidden)
+        .map(|(p, _)| p)
+}
+
+// Spot to turn into a unit at. Materialising inside an obstacle or a unit deals damage,
+// next to enemies or out of the zone is not much better.
+pub fn materialisation_spot(unit: &Unit) -> Vec2 {
+    let constants = get_constants();
+    let time_left = unit.remaining_spawn_time.unwrap_or(0.0);
+    let reach = time_left * constants.spawn_movement_speed;
+    let (center, radius) = predicted_zone(time_left);
+    let last_life = radius < constants.last_respawn_zone_radius;
+    let enemy_weight = if last_life {
+        LAST_LIFE_ENEMY_WEIGHT
+    } else {
+        ENEMY_WEIGHT
+    };
+
+    let mut spots = candidates(unit, reach);
+    spots.push(unit.position);
+    spots
+        .into_iter()
+        .map(|p| {
+            let collision = if collides(unit, &p) {
+                constants.spawn_collision_damage_per_second
+            } else {
+                0.0
+            };
+            let enemies = get_all_enemy_units()
+                .iter()
+                .map(|e| (e.firing_distance() + ZONE_MARGIN - e.position.distance(&p)).max(0.0))
+                .sum::<f64>()
+                * enemy_weight;
+            let zone = (p.distance(&center) + ZONE_MARGIN - radius).max(0.0) * 50.0;
+            let loot = get_loot()
+                .iter()
+                .filter(|l| l.position.distance(&p) < constants.view_distance / 2.0)
+                .count() as f64
+                * LOOT_WEIGHT;
+            let ally = unit
+                .my_closest_other_unit()
+                .map_or(0.0, |(_, a)| a.position.distance(&p) * ALLY_WEIGHT);
+            let score = loot + all_around_cover(&p) * COVER_WEIGHT
+                - ally
+                - collision
+                - enemies
+                - zone
+                - p.distance(&unit.position) / reach.max(1.0);
+            (p, score)
+        })
+        .max_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).unwrap())
+        .map(|(p, _)| p)
+        .unwrap_or(unit.position)
+}
+
+fn collides(unit: &Unit, p: &Vec2) -> bool {
+    let constants = get_constants();
+    constants
+        .obstacles
+        .iter()
+        .any(|o| o.position.distance(p) < o.radius + constants.unit_radius)
+        || get_game()
+            .units
+            .iter()
+            .chain(get_all_enemy_units().iter())
+            .filter(|u| u.id != unit.id)
+            .any(|u| u.position.distance(p) < constants.unit_radius * 2.0)
+}
+
+fn candidates(unit: &Unit, reach: f64) -> Vec<Vec2> {
+    let mut points = vec![];
+    for ring in 1..=RINGS {
+        for i in 0..DIRECTIONS {
+            let angle = 2.0 * PI * i as f64 / DIRECTIONS as f64;
+            points.push(rotate(
+                unit.position,
+                angle,
+                reach * ring as f64 / RINGS as f64,
+            ));
+        }
+    }
+    points
+}
@@ -0,0 +1,111 @@
+use crate::model::{Unit, Vec2};
+use crate::strategy::holder::get_constants;
+use crate::strategy::util::{centroid, intersects_with_obstacles_vec};
+use itertools::Itertools;
+use std::collections::HashSet;
+
+// units closer than that with nothing in between support each other in a fight
+const CLUSTER_RADIUS: f64 = 15.0;
+
+// Units fighting together on one side, enemies of different teams included. The id stays
+// the same across ticks while the cluster keeps some of its units.
+#[derive(Clone, Debug)]
+pub struct Cluster {
+    pub id: i32,
+    pub unit_ids: Vec<i32>,
+    pub center: Vec2,
+}
+
+impl Cluster {
+    pub fn contains(&self, unit_id: i32) -> bool {
+        self.unit_ids.contains(&unit_id)
+    }
+
+    pub fn units<'a>(&self, units: &[&'a Unit]) -> Vec<&'a Unit> {
+        units
+            .iter()
+            .filter(|u| self.contains(u.id))
+            .copied()
+            .collect_vec()
+    }
+}
+
+pub fn create_clusters(units: &[&Unit], previous: &[Cluster], next_id: &mut i32) -> Vec<Cluster> {
+    let mut clusters = connected_components(units)
+        .into_iter()
+        .map(|members| Cluster {
+            id: -1,
+            unit_ids: members.iter().map(|i| units[*i].id).collect_vec(),
+            center: centroid(members.iter().map(|i| units[*i].position)),
+        })
+        .collect_vec();
+
+    // the previous cluster sharing most units passes its id on
+    let matches = clusters
+        .iter()
+        .enumerate()
+        .flat_map(|(i, cluster)| {
+            previous.iter().map(move |p| {
+                let shared = cluster
+                    .unit_ids
+                    .iter()
+                    .filter(|id| p.contains(**id))
+                    .count();
+                (i, p.id, shared)
+            })
+        })
+        .filter(|(_, _, shared)| *shared > 0)
+        .sorted_by_key(|(_, _, shared)| std::cmp::Reverse(*shared))
+        .collect_vec();
+    let mut taken = HashSet::new();
+    for (i, id, _) in matches {
+        if clusters[i].id < 0 && !taken.contains(&id) {
+            clusters[i].id = id;
+            taken.insert(id);
+        }
+    }
+    for cluster in clusters.iter_mut().filter(|c| c.id < 0) {
+        cluster.id = *next_id;
+        *next_id += 1;
+    }
+    clusters
+}
+
+fn are_neighbours(a: &Unit, b: &Unit) -> bool {
+    a.position.distance(&b.position) < CLUSTER_RADIUS
+        && !intersects_with_obstacles_vec(&a.position, &b.position, &get_constants().obstacles)
+}
+
+// indices of the units in every cluster, units chained by neighbours end up together and a
+// lone unit gets a cluster of its own
+fn connected_components(units: &[&Unit]) -> Vec<Vec<usize>> {
+    let neighbours = (0..units.len())
+        .map(|i| {
+            (0..units.len())
+                .filter(|j| *j != i && are_neighbours(units[i], units[*j]))
+                .collect_vec()
+        })
+        .collect_vec();
+
+    let mut assigned = vec![false; units.len()];
+    let mut clusters = Vec::new();
+    for i in 0..units.len() {
+        if assigned[i] {
+            continue;
+        }
+        let mut members = Vec::new();
+        let mut queue = vec![i];
+        assigned[i] = true;
+        while let Some(p) = queue.pop() {
+            members.push(p);
+            for q in &neighbours[p] {
+                if !assigned[*q] {
+                    assigned[*q] = true;
+                    queue.push(*q);
+                }
+            }
+        }
+        clusters.push(members);
+    }
+    clusters
+}
@@ -1,31 +1,55 @@
 use crate::debug_interface::DebugInterface;
-use crate::debugging::{TRANSPARENT_BLACK, TRANSPARENT_GREEN, TRANSPARENT_ORANGE};
+use crate::debugging::{
+    TRANSPARENT_BLACK, TRANSPARENT_BLUE, TRANSPARENT_GREEN, TRANSPARENT_ORANGE,
+};
 use crate::model::{Obstacle, Unit, Vec2, WeaponProperties};
-use crate::strategy::behaviour::behaviour::{my_units_magnet_score, zone_penalty};
-use crate::strategy::holder::fight_sim::FightSimResult::{DRAW, LOST, WON};
-use crate::strategy::holder::{get_all_enemy_units, get_constants, get_game};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_enemy_clusters, get_game, get_my_clusters,
+};
+use crate::strategy::objective::required_win_probability;
 use crate::strategy::util::{intersects_with_obstacles_vec, rotate};
-use itertools::{all, Itertools};
-use std::cmp::max;
-use std::collections::HashSet;
-use std::fmt;
-use std::fmt::Formatter;
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
 
-const GROUP_RADIUS: f64 = 15.0;
+// groups further apart than their firing distance and that can't start a fight soon
+const REACH_MARGIN: f64 = 10.0;
+
+const ROLLOUTS: i32 = 30;
+// rollouts are shared by all the fights of a tick, the closest fights are played first
+const MAX_ROLLOUTS_PER_TICK: i32 = 150;
+const MIN_ROLLOUTS: i32 = 5;
+// every unit makes each rollout tick longer, only the closest third parties join
+const MAX_THIRD_PARTIES: usize = 4;
+const SIMULATION_TICKS: i32 = 300;
+// share of the max speed a target manages to sidestep while a projectile is flying
+const DODGE_FACTOR: f64 = 0.5;
+
+// our side in the simulation, every enemy team gets a side of its own
+const OURS: usize = 0;
 
 #[derive(Clone, Debug)]
-pub enum FightSimResult {
-    // number of people died
-    WON(i32),
-    LOST,
-    DRAW,
+pub struct FightOutcome {
+    // we survive and the target cluster doesn't, timeouts are split by the health left,
+    // third parties left standing take their share of the win
+    pub win_probability: f64,
+    // average health + shield left on our side
+    pub expected_hp: f64,
+    pub expected_enemy_hp: f64,
+    pub expected_third_party_hp: f64,
+    // share of the target cluster shots going to other teams instead of us
+    pub enemy_distraction: f64,
 }
 
 #[derive(Clone, Debug)]
 pub struct FightSim {
-    pub result: FightSimResult,
+    pub result: FightOutcome,
+    // enemy cluster the fight is against
+    pub cluster_id: i32,
     pub allies: HashSet<i32>,
     pub enemies: HashSet<i32>,
+    pub third_parties: HashSet<i32>,
 }
 
 impl FightSim {
@@ -35,188 +59,471 @@ impl FightSim {
             .filter(|e| self.enemies.contains(&e.id))
             .collect_vec()
     }
+
+    pub fn is_favourable(&self) -> bool {
+        self.result.win_probability >= required_win_probability()
+    }
 }
 
 pub fn create_fight_simulations(
     debug_interface: &mut Option<&mut DebugInterface>,
 ) -> Vec<FightSim> {
-    let my_groups = get_game()
-        .my_units()
+    let my_units = get_game().my_units();
+    let enemies = get_all_enemy_units().iter().collect_vec();
+
+    let fights = get_my_clusters()
         .iter()
-        .map(|unit| {
-            let mut a = get_my_other_units_nearby(unit);
-            a.push(unit);
-            a
+        .flat_map(|my_cluster| get_enemy_clusters().iter().map(move |c| (my_cluster, c)))
+        .map(|(my_cluster, cluster)| {
+            (
+                my_cluster.units(&my_units),
+                cluster,
+                cluster.units(&enemies),
+            )
+        })
+        .filter(|(my_group, _, enemy_group)| in_reach(my_group, enemy_group))
+        .sorted_by(|(m1, _, e1), (m2, _, e2)| {
+            closest_distance(m1, e1).total_cmp(&closest_distance(m2, e2))
         })
-        .unique_by(|a| a.iter().map(|e| e.id.to_string()).sorted().join(","))
         .collect_vec();
-    // println!("calculating sim for groups: {}", my_groups.iter().map(|e| e.len().to_string()).join(", "));
-
-    let enemy_groups = find_enemy_groups();
+    let rollouts_per_fight =
+        (MAX_ROLLOUTS_PER_TICK / fights.len().max(1) as i32).clamp(MIN_ROLLOUTS, ROLLOUTS);
+    let mut rollouts_left = MAX_ROLLOUTS_PER_TICK;
 
     let mut sims = Vec::new();
-    for my_group in &my_groups {
-        for enemy_group in &enemy_groups {
-            let sim_res = simulation(&my_group, &enemy_group);
-            if let Some(debug) = debug_interface.as_mut() {
-                for x in enemy_group {
-                    let color = match sim_res {
-                        WON(_) => TRANSPARENT_GREEN,
-                        DRAW => TRANSPARENT_ORANGE,
-                        LOST => TRANSPARENT_BLACK,
-                    };
-                    debug.add_circle(x.position, 1.5, color);
-                }
+    for (my_group, cluster, enemy_group) in fights {
+        let rollouts = rollouts_per_fight.min(rollouts_left);
+        if rollouts < MIN_ROLLOUTS {
+            break;
+        }
+        rollouts_left -= rollouts;
+        // enemy groups in reach of the fight are expected to join it
+        let involved = my_group
+            .iter()
+            .chain(enemy_group.iter())
+            .copied()
+            .collect_vec();
+        let third_parties = get_enemy_clusters()
+            .iter()
+            .filter(|c| c.id != cluster.id)
+            .map(|c| c.units(&enemies))
+            .filter(|g| in_reach(g, &involved))
+            .flatten()
+            .sorted_by(|a, b| {
+                closest_distance(&[a], &involved).total_cmp(&closest_distance(&[b], &involved))
+            })
+            .take(MAX_THIRD_PARTIES)
+            .collect_vec();
+        let sim_res = simulation(&my_group, &enemy_group, &third_parties, rollouts);
+        if let Some(debug) = debug_interface.as_mut() {
+            for x in &enemy_group {
+                let color = if sim_res.win_probability >= 0.6 {
+                    TRANSPARENT_GREEN
+                } else if sim_res.win_probability >= 0.4 {
+                    TRANSPARENT_ORANGE
+                } else {
+                    TRANSPARENT_BLACK
+                };
+                debug.add_circle(x.position, 1.5, color);
+            }
+            for x in &third_parties {
+                debug.add_circle(x.position, 2.0, TRANSPARENT_BLUE);
             }
-            sims.push(FightSim {
-                result: sim_res,
-                allies: my_group.iter().map(|e| e.id).collect(),
-                enemies: enemy_group.iter().map(|e| e.id).collect(),
-            });
         }
+        sims.push(FightSim {
+            result: sim_res,
+            cluster_id: cluster.id,
+            allies: my_group.iter().map(|e| e.id).collect(),
+            enemies: enemy_group.iter().map(|e| e.id).collect(),
+            third_parties: third_parties.iter().map(|e| e.id).collect(),
+        });
     }
-    return sims;
+    sims
 }
 
-fn find_enemy_groups() -> Vec<Vec<&'static Unit>> {
-    let mut groups = Vec::new();
-    for (_, units) in &get_all_enemy_units()
-        .iter()
-        .filter(|e| e.remaining_spawn_time.is_none())
-        .group_by(|e| e.player_id)
-    {
-        groups.push(units.collect_vec());
-    }
-    groups
+// some units of the groups are in firing distance of each other or close to it
+fn in_reach(a: &[&Unit], b: &[&Unit]) -> bool {
+    a.iter().any(|u| {
+        b.iter().any(|e| {
+            u.position.distance(&e.position)
+                < e.firing_distance().max(u.firing_distance()) + REACH_MARGIN
+        })
+    })
 }
 
-fn get_my_other_units_nearby(unit: &Unit) -> Vec<&Unit> {
-    unit.my_other_units()
-        .into_iter()
-        .filter(|e| e.remaining_spawn_time.is_none())
-        .filter(|e| e.position.distance(&unit.position) < GROUP_RADIUS)
-        .collect_vec()
+fn closest_distance(a: &[&Unit], b: &[&Unit]) -> f64 {
+    a.iter()
+        .flat_map(|u| b.iter().map(|e| u.position.distance(&e.position)))
+        .fold(f64::INFINITY, f64::min)
 }
 
-struct FightProps {
+#[derive(Clone)]
+struct Fighter {
+    side: usize,
+    is_target: bool,
     position: Vec2,
-    weapon: WeaponProperties,
+    weapon: Option<WeaponProperties>,
     ammo: i32,
     aim: f64,
     health: f64,
-    fire_tick: i32,
-}
-
-impl fmt::Display for FightProps {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "tick {}, rate {}, ammo {}, health {}",
-            self.fire_tick,
-            self.weapon.get_fire_rate_in_ticks(),
-            self.ammo,
-            self.health
-        )
-    }
-}
-
-pub fn simulation(u1: &Vec<&Unit>, u2: &Vec<&Unit>) -> FightSimResult {
-    fn fighter_props(team: &Vec<&Unit>) -> Vec<FightProps> {
-        team.iter()
-            .filter(|e| e.weapon.is_some())
-            .filter(|e| e.ammo[e.weapon.unwrap() as usize] != 0)
-            .map(|e| FightProps {
-                position: e.position,
-                weapon: get_constants().weapons[e.weapon.unwrap() as usize].clone(),
-                aim: e.aim,
-                ammo: e.ammo[e.weapon.unwrap() as usize],
-                health: e.health + e.shield,
-                fire_tick: -(max(get_game().current_tick, e.next_shot_tick)
-                    - get_game().current_tick),
-                // fire_tick: 0,
-            })
-            .collect_vec()
+    shield: f64,
+    shield_potions: i32,
+    next_shot_tick: i32,
+    regeneration_tick: i32,
+    potion_finish_tick: Option<i32>,
+}
+
+impl Fighter {
+    fn new(unit: &Unit, side: usize, is_target: bool) -> Self {
+        let tick = get_game().current_tick;
+        Fighter {
+            side,
+            is_target,
+            position: unit.position,
+            weapon: unit
+                .weapon
+                .map(|w| get_constants().weapons[w as usize].clone()),
+            ammo: unit.ammo_for_current_weapon(),
+            aim: unit.aim,
+            health: unit.health,
+            shield: unit.shield,
+            shield_potions: unit.shield_potions,
+            next_shot_tick: unit.next_shot_tick - tick,
+            regeneration_tick: unit.health_regeneration_start_tick - tick,
+            potion_finish_tick: None,
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.health > 0.0
+    }
+
+    fn hp(&self) -> f64 {
+        if self.is_alive() {
+            self.health + self.shield
+        } else {
+            0.0
+        }
+    }
+
+    fn can_shoot(&self) -> bool {
+        self.is_alive() && self.weapon.is_some() && self.ammo > 0
     }
-    let mut allies = fighter_props(u1);
-    if allies.is_empty() {
-        return LOST;
+
+    fn firing_distance(&self) -> f64 {
+        self.weapon
+            .as_ref()
+            .map(|w| w.firing_distance())
+            .unwrap_or(0.0)
     }
-    let mut enemies = fighter_props(u2);
-    if enemies.is_empty() {
-        return WON(0);
+
+    fn take_damage(&mut self, damage: f64, tick: i32) {
+        let constants = get_constants();
+        let absorbed = self.shield.min(damage);
+        self.shield -= absorbed;
+        self.health -= damage - absorbed;
+        self.regeneration_tick =
+            tick + (constants.health_regeneration_delay * constants.ticks_per_second).ceil() as i32;
     }
+}
+
+struct PendingHit {
+    tick: i32,
+    damage: f64,
+    target: usize,
+}
 
-    // println!("START!");
-    while !allies.is_empty() && !enemies.is_empty() {
-        // println!("{}, enemies {}",
-        //          allies.iter().map(|e| e.to_string()).join(","),
-        //          enemies.iter().map(|e| e.to_string()).join(","));
-        let min_fire_rate = allies
+// Plays the fight between our group, the target cluster and every third party around
+// tick by tick a number of times with randomized spread and dodging. Everybody shoots
+// the weakest unit of any other team it has line of fire to.
+pub fn simulation(
+    ours: &[&Unit],
+    target: &[&Unit],
+    third_parties: &[&Unit],
+    rollouts: i32,
+) -> FightOutcome {
+    let teams = target
+        .iter()
+        .chain(third_parties.iter())
+        .map(|u| u.player_id)
+        .unique()
+        .sorted()
+        .collect_vec();
+    let side = |u: &Unit| OURS + 1 + teams.iter().position(|p| *p == u.player_id).unwrap();
+    let mut fighters = ours
+        .iter()
+        .map(|u| Fighter::new(u, OURS, false))
+        .collect_vec();
+    fighters.extend(target.iter().map(|u| Fighter::new(u, side(u), true)));
+    fighters.extend(
+        third_parties
             .iter()
-            .map(|e| e.weapon.get_fire_rate_in_ticks())
-            .chain(enemies.iter().map(|e| e.weapon.get_fire_rate_in_ticks()))
-            .min()
-            .unwrap();
-        fn process_tick(
-            fire_rate: i32,
-            cur_team: &mut Vec<FightProps>,
-            other_team: &mut Vec<FightProps>,
-        ) {
-            for mut ally in cur_team {
-                if ally.ammo == 0 {
-                    continue;
-                }
-                ally.fire_tick += fire_rate;
-                if ally.fire_tick >= ally.weapon.get_fire_rate_in_ticks() {
-                    ally.fire_tick -= ally.weapon.get_fire_rate_in_ticks();
-                    for mut enemy in &mut *other_team {
-                        if enemy.health > 0.0 {
-                            enemy.health -= ally.weapon.projectile_damage
-                                * chance_to_hit(&enemy.position, &ally.position, &enemy.weapon);
-                            break;
-                        }
-                    }
-                    ally.ammo -= 1
-                }
-            }
+            .map(|u| Fighter::new(u, side(u), false)),
+    );
+    let obstacles = obstacles_around(fighters.iter().map(|f| f.position));
+
+    let mut rng = StdRng::seed_from_u64(get_game().current_tick as u64);
+    let mut win_sum = 0.0;
+    let mut hp_sum = 0.0;
+    let mut enemy_hp_sum = 0.0;
+    let mut third_party_hp_sum = 0.0;
+    // shots by the shooter index and the side it shot at
+    let mut shots: HashMap<(usize, usize), i32> = HashMap::new();
+    for _ in 0..rollouts {
+        let left = rollout(fighters.clone(), &obstacles, &mut shots, &mut rng);
+        let hp = total_hp(&left, |f| f.side == OURS);
+        let enemy_hp = total_hp(&left, |f| f.is_target);
+        let third_party_hp = total_hp(&left, |f| f.side != OURS && !f.is_target);
+        let alive = |is: fn(&Fighter) -> bool| left.iter().any(|f| is(f) && f.is_alive());
+        win_sum += match (alive(|f| f.side == OURS), alive(|f| f.is_target)) {
+            (true, false) => hp / (hp + third_party_hp),
+            (false, true) => 0.0,
+            _ if hp + enemy_hp + third_party_hp > 0.0 => hp / (hp + enemy_hp + third_party_hp),
+            _ => 0.5,
+        };
+        hp_sum += hp;
+        enemy_hp_sum += enemy_hp;
+        third_party_hp_sum += third_party_hp;
+    }
+
+    let target_shots = shots
+        .iter()
+        .filter(|((from, _), _)| fighters[*from].is_target)
+        .map(|(_, count)| *count)
+        .sum::<i32>();
+    let distracted_shots = shots
+        .iter()
+        .filter(|((from, to), _)| fighters[*from].is_target && *to != OURS)
+        .map(|(_, count)| *count)
+        .sum::<i32>();
+    FightOutcome {
+        win_probability: win_sum / rollouts as f64,
+        expected_hp: hp_sum / rollouts as f64,
+        expected_enemy_hp: enemy_hp_sum / rollouts as f64,
+        expected_third_party_hp: third_party_hp_sum / rollouts as f64,
+        enemy_distraction: if target_shots > 0 {
+            distracted_shots as f64 / target_shots as f64
+        } else {
+            0.0
+        },
+    }
+}
+
+fn total_hp(fighters: &[Fighter], filter: impl Fn(&Fighter) -> bool) -> f64 {
+    fighters.iter().filter(|f| filter(f)).map(|f| f.hp()).sum()
+}
+
+// runs until a single side is left standing, the cleanup of the winner included
+fn rollout(
+    mut fighters: Vec<Fighter>,
+    obstacles: &[Obstacle],
+    shots: &mut HashMap<(usize, usize), i32>,
+    rng: &mut StdRng,
+) -> Vec<Fighter> {
+    let mut hits = Vec::new();
+    let zone = &get_game().zone;
+    let zone_shrink_per_tick = get_constants().zone_speed / get_constants().ticks_per_second;
+
+    for tick in 0..SIMULATION_TICKS {
+        let zone_radius = zone.current_radius - zone_shrink_per_tick * tick as f64;
+        for i in 0..fighters.len() {
+            process_fighter_tick(tick, i, &mut fighters, &mut hits, shots, obstacles, rng);
         }
-        process_tick(min_fire_rate, &mut allies, &mut enemies);
-        process_tick(min_fire_rate, &mut enemies, &mut allies);
-
-        let mut any_with_ammo = false;
-        for i in (0..allies.len()).rev() {
-            any_with_ammo = any_with_ammo || allies[i].ammo != 0;
-            if allies[i].health <= 0.0 {
-                allies.remove(i);
-            }
+        apply_hits(tick, &mut fighters, &mut hits);
+        for f in fighters.iter_mut() {
+            process_environment(tick, f, &zone.current_center, zone_radius);
         }
-        for i in (0..enemies.len()).rev() {
-            any_with_ammo = any_with_ammo || enemies[i].ammo != 0;
-            if enemies[i].health <= 0.0 {
-                enemies.remove(i);
-            }
+
+        let sides_alive = fighters
+            .iter()
+            .filter(|f| f.is_alive())
+            .map(|f| f.side)
+            .unique()
+            .count();
+        let anyone_can_shoot = fighters.iter().any(|f| f.can_shoot());
+        if sides_alive <= 1 || (!anyone_can_shoot && hits.is_empty()) {
+            break;
         }
-        if !any_with_ammo {
-            return DRAW;
-        };
     }
-    // println!("END!");
+    fighters
+}
 
-    if enemies.is_empty() {
-        if allies.is_empty() {
-            DRAW
-        } else {
-            WON((u1.len() - allies.len()) as i32)
+fn process_fighter_tick(
+    tick: i32,
+    index: usize,
+    fighters: &mut [Fighter],
+    hits: &mut Vec<PendingHit>,
+    shots: &mut HashMap<(usize, usize), i32>,
+    obstacles: &[Obstacle],
+    rng: &mut StdRng,
+) {
+    let constants = get_constants();
+    if !fighters[index].is_alive() {
+        return;
+    }
+    let closest = closest_enemy(&fighters[index], fighters).copied();
+    let weapon = match &fighters[index].weapon {
+        Some(w) if fighters[index].ammo > 0 => w.clone(),
+        _ => {
+            step(&mut fighters[index], closest.as_ref(), obstacles, rng);
+            return;
+        }
+    };
+    let fighter = &fighters[index];
+    let target = fighters
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.side != fighter.side && e.is_alive())
+        .filter(|(_, e)| e.position.distance(&fighter.position) < weapon.firing_distance())
+        .filter(|(_, e)| !intersects_with_obstacles_vec(&fighter.position, &e.position, obstacles))
+        .min_by(|(_, a), (_, b)| a.hp().partial_cmp(&b.hp()).unwrap())
+        .map(|(i, _)| (i, fighters[i].side, fighters[i].position));
+
+    let aim_per_tick = 1.0 / weapon.ticks_to_aim().max(1) as f64;
+    let fighter = &mut fighters[index];
+    match target {
+        None => {
+            fighter.aim = (fighter.aim - aim_per_tick).max(0.0);
+            step(fighter, closest.as_ref(), obstacles, rng);
+        }
+        Some((target, target_side, target_position)) => {
+            // aiming interrupts drinking
+            fighter.potion_finish_tick = None;
+            fighter.aim = (fighter.aim + aim_per_tick).min(1.0);
+            step(fighter, Some(&target_position), obstacles, rng);
+            if fighter.aim >= 1.0 && tick >= fighter.next_shot_tick {
+                fighter.ammo -= 1;
+                fighter.next_shot_tick = tick + weapon.get_fire_rate_in_ticks();
+                *shots.entry((index, target_side)).or_insert(0) += 1;
+                let distance = fighter.position.distance(&target_position);
+                let flight_time = distance / weapon.projectile_speed;
+                let half_spread = weapon.spread.to_radians() / 2.0;
+                let offset = distance * rng.gen_range(-half_spread..=half_spread).sin();
+                let dodge = rng.gen_range(-1.0..=1.0)
+                    * constants.max_unit_forward_speed
+                    * flight_time
+                    * DODGE_FACTOR;
+                if (offset - dodge).abs() < constants.unit_radius {
+                    hits.push(PendingHit {
+                        tick: tick + (flight_time * constants.ticks_per_second).ceil() as i32,
+                        damage: weapon.projectile_damage,
+                        target,
+                    });
+                }
+            }
         }
+    }
+}
+
+// moves to the firing distance of the goal and strafes once there
+fn step(fighter: &mut Fighter, goal: Option<&Vec2>, obstacles: &[Obstacle], rng: &mut StdRng) {
+    let constants = get_constants();
+    let goal = match goal {
+        None => return,
+        Some(goal) => goal,
+    };
+    let aim_modifier = fighter
+        .weapon
+        .as_ref()
+        .map(|w| 1.0 - (1.0 - w.aim_movement_speed_modifier) * fighter.aim)
+        .unwrap_or(1.0);
+    let speed = constants.max_unit_forward_speed / constants.ticks_per_second * aim_modifier;
+    // the angle of a zero vector is undefined
+    if fighter.position.distance(goal) < f64::EPSILON {
+        return;
+    }
+    let angle_to_goal = (*goal - fighter.position).angle();
+    let angle = if fighter.position.distance(goal) > fighter.firing_distance() * 0.9 {
+        angle_to_goal
+    } else if rng.gen_bool(0.5) {
+        angle_to_goal + std::f64::consts::FRAC_PI_2
     } else {
-        LOST
+        angle_to_goal - std::f64::consts::FRAC_PI_2
+    };
+    let next = rotate(fighter.position, angle, speed);
+    if obstacles
+        .iter()
+        .all(|o| o.position.distance(&next) >= o.radius + constants.unit_radius)
+    {
+        fighter.position = next;
+    }
+}
+
+fn process_environment(tick: i32, fighter: &mut Fighter, zone_center: &Vec2, zone_radius: f64) {
+    let constants = get_constants();
+    if !fighter.is_alive() {
+        return;
+    }
+    if fighter.position.distance(zone_center) > zone_radius {
+        fighter.take_damage(
+            constants.zone_damage_per_second / constants.ticks_per_second,
+            tick,
+        );
+    }
+    if tick >= fighter.regeneration_tick {
+        fighter.health = (fighter.health
+            + constants.health_regeneration_per_second / constants.ticks_per_second)
+            .min(constants.unit_health);
+    }
+    match fighter.potion_finish_tick {
+        Some(finish) if finish <= tick => {
+            fighter.potion_finish_tick = None;
+            fighter.shield_potions -= 1;
+            fighter.shield =
+                (fighter.shield + constants.shield_per_potion).min(constants.max_shield);
+        }
+        None if fighter.aim == 0.0
+            && fighter.shield_potions > 0
+            && fighter.shield < constants.max_shield =>
+        {
+            fighter.potion_finish_tick = Some(
+                tick + (constants.shield_potion_use_time * constants.ticks_per_second).ceil()
+                    as i32,
+            );
+        }
+        _ => {}
     }
 }
 
-fn chance_to_hit(shooter_pos: &Vec2, target_pos: &Vec2, weapon: &WeaponProperties) -> f64 {
-    let distance = shooter_pos.distance(target_pos);
-    let angle = (target_pos.clone() - shooter_pos.clone()).angle();
-    let left = rotate(shooter_pos.clone(), angle - weapon.spread / 2.0, distance);
-    let right = rotate(shooter_pos.clone(), angle + weapon.spread / 2.0, distance);
-    2.0 * get_constants().unit_radius / (left.distance(target_pos) + right.distance(target_pos))
+fn apply_hits(tick: i32, fighters: &mut [Fighter], hits: &mut Vec<PendingHit>) {
+    hits.retain(|hit| {
+        if hit.tick > tick {
+            return true;
+        }
+        if fighters[hit.target].is_alive() {
+            fighters[hit.target].take_damage(hit.damage, tick);
+        }
+        false
+    });
+}
+
+fn closest_enemy<'a>(fighter: &Fighter, fighters: &'a [Fighter]) -> Option<&'a Vec2> {
+    fighters
+        .iter()
+        .filter(|e| e.side != fighter.side && e.is_alive())
+        .map(|e| &e.position)
+        .min_by(|a, b| {
+            a.distance(&fighter.position)
+                .partial_cmp(&b.distance(&fighter.position))
+                .unwrap()
+        })
+}
+
+fn obstacles_around(positions: impl Iterator<Item = Vec2>) -> Vec<Obstacle> {
+    let positions = positions.collect_vec();
+    let max_distance = get_constants()
+        .weapons
+        .iter()
+        .map(|w| w.firing_distance())
+        .fold(0.0, f64::max);
+    get_constants()
+        .obstacles
+        .iter()
+        .filter(|o| {
+            positions
+                .iter()
+                .any(|p| p.distance(&o.position) < max_distance + o.radius)
+        })
+        .cloned()
+        .collect_vec()
 }
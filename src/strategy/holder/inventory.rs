@@ -0,0 +1,239 @@
+use super::inside_vision;
+use crate::model::{ActionType, Game, Item, Loot, Projectile, Unit};
+use crate::strategy::holder::get_constants;
+use crate::strategy::holder::loot_memory::LOOTING_DISTANCE;
+use crate::strategy::holder::tracker::EnemyBelief;
+use itertools::Itertools;
+
+// confidence left after a tick of not seeing the enemy
+const CONFIDENCE_DECAY: f64 = 0.998;
+// every guessed event makes the estimate less reliable
+const EVENT_CONFIDENCE: f64 = 0.9;
+
+// What we think an enemy carries. Exact while we see the enemy, then updated from what we
+// notice: shots, our hits, potions and loot which disappeared next to it.
+#[derive(Clone, Debug)]
+pub struct EnemyInventory {
+    pub unit_id: i32,
+    pub health: f64,
+    pub shield: f64,
+    pub shield_potions: i32,
+    pub ammo: Vec<i32>,
+    pub weapon: Option<i32>,
+    // 1 - seen right now, 0 - nothing but the prior
+    pub confidence: f64,
+    health_regeneration_start_tick: i32,
+    potion_finish_tick: Option<i32>,
+}
+
+impl EnemyInventory {
+    fn seen(unit: &Unit) -> Self {
+        EnemyInventory {
+            unit_id: unit.id,
+            health: unit.health,
+            shield: unit.shield,
+            shield_potions: unit.shield_potions,
+            ammo: unit.ammo.clone(),
+            weapon: unit.weapon,
+            confidence: 1.0,
+            health_regeneration_start_tick: unit.health_regeneration_start_tick,
+            potion_finish_tick: unit
+                .action
+                .as_ref()
+                .filter(|a| matches!(a.action_type, ActionType::UseShieldPotion))
+                .map(|a| a.finish_tick),
+        }
+    }
+
+    // an average enemy in the middle of the game, not the best case
+    fn prior(unit_id: i32, weapon: Option<i32>) -> Self {
+        let constants = get_constants();
+        EnemyInventory {
+            unit_id,
+            health: constants.unit_health,
+            shield: constants.max_shield / 2.0,
+            shield_potions: constants.max_shield_potions_in_inventory / 2,
+            ammo: constants
+                .weapons
+                .iter()
+                .map(|w| w.max_inventory_ammo / 2)
+                .collect_vec(),
+            weapon: weapon.or(constants.starting_weapon),
+            confidence: 0.0,
+            health_regeneration_start_tick: 0,
+            potion_finish_tick: None,
+        }
+    }
+
+    fn pass_tick(&mut self, tick: i32) {
+        let constants = get_constants();
+        self.confidence *= CONFIDENCE_DECAY;
+        if tick >= self.health_regeneration_start_tick {
+            self.health = (self.health
+                + constants.health_regeneration_per_second / constants.ticks_per_second)
+                .min(constants.unit_health);
+        }
+        if self.potion_finish_tick.is_some_and(|t| t <= tick) {
+            self.potion_finish_tick = None;
+            self.shield = (self.shield + constants.shield_per_potion).min(constants.max_shield);
+            self.shield_potions = (self.shield_potions - 1).max(0);
+        }
+    }
+
+    fn shot(&mut self, weapon: i32) {
+        self.weapon = Some(weapon);
+        if let Some(ammo) = self.ammo.get_mut(weapon as usize) {
+            *ammo = (*ammo - 1).max(0);
+        }
+    }
+
+    fn damaged(&mut self, damage: f64, tick: i32) {
+        let constants = get_constants();
+        let to_shield = damage.min(self.shield);
+        self.shield -= to_shield;
+        self.health = (self.health - (damage - to_shield)).max(0.0);
+        self.health_regeneration_start_tick =
+            tick + (constants.health_regeneration_delay * constants.ticks_per_second) as i32;
+        self.confidence *= EVENT_CONFIDENCE;
+    }
+
+    fn looted(&mut self, item: &Item) {
+        let constants = get_constants();
+        match item {
+            Item::Weapon { type_index } => self.weapon = Some(*type_index),
+            Item::ShieldPotions { amount } => {
+                self.shield_potions =
+                    (self.shield_potions + amount).min(constants.max_shield_potions_in_inventory)
+            }
+            Item::Ammo {
+                weapon_type_index,
+                amount,
+            } => {
+                let max = constants.weapons[*weapon_type_index as usize].max_inventory_ammo;
+                if let Some(ammo) = self.ammo.get_mut(*weapon_type_index as usize) {
+                    *ammo = (*ammo + amount).min(max);
+                }
+            }
+        }
+        self.confidence *= EVENT_CONFIDENCE;
+    }
+
+    // the estimate weighted against the prior by how sure we are
+    pub fn apply(&self, unit: &mut Unit) {
+        let prior = EnemyInventory::prior(self.unit_id, self.weapon);
+        let c = self.confidence;
+        let blend = |estimate: f64, prior: f64| estimate * c + prior * (1.0 - c);
+        unit.health = blend(self.health, prior.health);
+        unit.shield = blend(self.shield, prior.shield);
+        unit.shield_potions =
+            blend(self.shield_potions as f64, prior.shield_potions as f64).round() as i32;
+        unit.ammo = self
+            .ammo
+            .iter()
+            .zip(prior.ammo.iter())
+            .map(|(a, p)| blend(*a as f64, *p as f64).round() as i32)
+            .collect_vec();
+        unit.weapon = self.weapon;
+    }
+}
+
+pub fn update_inventories(
+    previous: &[EnemyInventory],
+    game: &Game,
+    beliefs: &[EnemyBelief],
+    previous_projectiles: &[Projectile],
+    previous_loot: &[Loot],
+) -> Vec<EnemyInventory> {
+    let seen = game.enemy_units();
+    let mut inventories = seen.iter().map(|u| EnemyInventory::seen(u)).collect_vec();
+    for inventory in previous {
+        let is_seen = seen.iter().any(|u| u.id == inventory.unit_id);
+        // ids of enemies we only heard are not stable, they live as long as the belief
+        let is_tracked =
+            inventory.unit_id >= 0 || beliefs.iter().any(|b| b.unit.id == inventory.unit_id);
+        if !is_seen && is_tracked {
+            let mut inventory = inventory.clone();
+            inventory.pass_tick(game.current_tick);
+            inventories.push(inventory);
+        }
+    }
+    for belief in beliefs {
+        if inventories.iter().all(|i| i.unit_id != belief.unit.id) {
+            inventories.push(EnemyInventory::prior(belief.unit.id, belief.unit.weapon));
+        }
+    }
+
+    let unseen = |id: i32| seen.iter().all(|u| u.id != id);
+
+    // shots of enemies out of sight
+    for projectile in game
+        .projectiles
+        .iter()
+        .filter(|p| p.shooter_player_id != game.my_id && unseen(p.shooter_id))
+        .filter(|p| previous_projectiles.iter().all(|pp| pp.id != p.id))
+    {
+        if let Some(inventory) = inventories
+            .iter_mut()
+            .find(|i| i.unit_id == projectile.shooter_id)
+        {
+            inventory.shot(projectile.weapon_type_index);
+        }
+    }
+
+    // our projectiles which disappeared inside an enemy we don't see
+    for projectile in previous_projectiles
+        .iter()
+        .filter(|p| p.shooter_player_id == game.my_id)
+        .filter(|p| game.projectiles.iter().all(|gp| gp.id != p.id))
+    {
+        let position = match projectile.position_after_ticks(1) {
+            None => continue,
+            Some(position) => position,
+        };
+        // same check the projectile memory drops projectiles by, so a hit is counted once
+        let hit = beliefs
+            .iter()
+            .filter(|b| unseen(b.unit.id))
+            .find(|b| b.unit.position.distance(&position) < get_constants().unit_radius);
+        if let Some(belief) = hit {
+            let damage =
+                get_constants().weapons[projectile.weapon_type_index as usize].projectile_damage;
+            if let Some(inventory) = inventories.iter_mut().find(|i| i.unit_id == belief.unit.id) {
+                inventory.damaged(damage, game.current_tick);
+            }
+        }
+    }
+
+    // loot which disappeared in front of us and none of my units took
+    for loot in previous_loot
+        .iter()
+        .filter(|l| game.loot.iter().all(|gl| gl.id != l.id))
+        .filter(|l| inside_vision(game, &l.position))
+    {
+        let looting_distance = get_constants().unit_radius + LOOTING_DISTANCE;
+        if game
+            .my_units()
+            .iter()
+            .any(|u| u.position.distance(&loot.position) < looting_distance)
+        {
+            continue;
+        }
+        let looter = beliefs
+            .iter()
+            .filter(|b| b.unit.position.distance(&loot.position) < looting_distance + b.uncertainty)
+            .min_by(|a, b| {
+                let da = a.unit.position.distance(&loot.position);
+                let db = b.unit.position.distance(&loot.position);
+                da.partial_cmp(&db).unwrap()
+            });
+        if let Some(belief) = looter {
+            if let Some(inventory) = inventories
+                .iter_mut()
+                .find(|i| i.unit_id == belief.unit.id && unseen(i.unit_id))
+            {
+                inventory.looted(&loot.item);
+            }
+        }
+    }
+    inventories
+}
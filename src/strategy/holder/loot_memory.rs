@@ -0,0 +1,60 @@
+use super::inside_vision;
+use crate::model::{Game, Loot};
+use crate::strategy::holder::get_constants;
+use crate::strategy::holder::tracker::EnemyBelief;
+use itertools::Itertools;
+
+// loot nobody is around still can be taken by somebody we never noticed
+const CONFIDENCE_DECAY: f64 = 0.9995;
+// chance per tick that an enemy standing on the loot takes it
+const TAKE_CHANCE: f64 = 0.05;
+// enemies this far from the loot are not looting it
+pub const LOOTING_DISTANCE: f64 = 2.0;
+const MIN_CONFIDENCE: f64 = 0.2;
+
+#[derive(Clone, Debug)]
+pub struct RememberedLoot {
+    pub loot: Loot,
+    // chance the loot is still there
+    pub confidence: f64,
+    pub last_seen_tick: i32,
+}
+
+pub fn update_loot_memory(
+    previous: &[RememberedLoot],
+    game: &Game,
+    beliefs: &[EnemyBelief],
+) -> Vec<RememberedLoot> {
+    let mut memory = game
+        .loot
+        .iter()
+        .map(|l| RememberedLoot {
+            loot: l.clone(),
+            confidence: 1.0,
+            last_seen_tick: game.current_tick,
+        })
+        .collect_vec();
+    for remembered in previous {
+        if game.loot.iter().any(|l| l.id == remembered.loot.id) {
+            continue;
+        }
+        // we look at the place and the loot is not there
+        if inside_vision(game, &remembered.loot.position) {
+            continue;
+        }
+        let mut remembered = remembered.clone();
+        remembered.confidence *= CONFIDENCE_DECAY;
+        for belief in beliefs {
+            let reach = get_constants().unit_radius + LOOTING_DISTANCE + belief.uncertainty;
+            let distance = belief.unit.position.distance(&remembered.loot.position);
+            if distance < reach {
+                // a vague belief around the loot is less likely to be standing on it
+                remembered.confidence *= 1.0 - TAKE_CHANCE * (1.0 - distance / reach);
+            }
+        }
+        if remembered.confidence >= MIN_CONFIDENCE {
+            memory.push(remembered);
+        }
+    }
+    memory
+}
@@ -1,38 +1,139 @@
+pub mod clustering;
 pub mod fight_sim;
+pub mod inventory;
+pub mod loot_memory;
+pub mod tracker;
 
 use crate::debug_interface::DebugInterface;
-use crate::debugging::{BLUE, RED};
+use crate::debugging::RED;
 use crate::model::{Constants, Game, Loot, Obstacle, Projectile, Unit, Vec2};
+use crate::strategy::cover::{build_cover_map, CoverMap};
+use crate::strategy::exploration::ExplorationGrid;
+use crate::strategy::holder::clustering::{create_clusters, Cluster};
 use crate::strategy::holder::fight_sim::{create_fight_simulations, FightSim};
+use crate::strategy::holder::inventory::{update_inventories, EnemyInventory};
+use crate::strategy::holder::loot_memory::{update_loot_memory, RememberedLoot};
+use crate::strategy::holder::tracker::{projectile_observations, update_beliefs, EnemyBelief};
+use crate::strategy::objective::Objective;
+use crate::strategy::sound::{interpret_all, is_unexplained, sound_observations, SoundInference};
+use crate::strategy::team::TeamPlan;
+use crate::strategy::zone::ZonePlan;
 use itertools::Itertools;
 use std::collections::HashMap;
-use crate::strategy::util::intersects_with_obstacles_vec;
+
+// how long units keep turning to a sound nobody explained
+const SOUND_MEMORY_TICKS: i32 = 30;
 
 static mut GAME: Game = Game::const_default();
 static mut CONSTANTS: Constants = Constants::const_default();
+static mut COVER_MAP: CoverMap = CoverMap::const_default();
+static mut EXPLORATION_GRID: ExplorationGrid = ExplorationGrid::const_default();
 static mut NEAREST_OBSTACLES: Vec<(i32, Vec<Obstacle>)> = vec![];
 
-static mut LOOT_TO_TICK: Vec<(i32, Loot)> = vec![];
+static mut LOOT_MEMORY: Vec<RememberedLoot> = vec![];
 static mut LOOT: Vec<Loot> = vec![];
-static mut BOOKED_LOOT: Vec<i32> = vec![];
-
-static mut UNIT_TO_TICK: Vec<(i32, Unit)> = vec![];
+// items every unit is going to pick up, in order
+static mut LOOT_ROUTES: Vec<(i32, Vec<Loot>)> = vec![];
+
+static mut ENEMY_BELIEFS: Vec<EnemyBelief> = vec![];
+// projectiles already used to locate their shooters
+static mut FUSED_PROJECTILES: Vec<i32> = vec![];
+static mut ENEMY_INVENTORIES: Vec<EnemyInventory> = vec![];
+static mut UNEXPLAINED_SOUNDS: Vec<SoundInference> = vec![];
 static mut UNITS: Vec<Unit> = vec![];
 
+static mut MY_CLUSTERS: Vec<Cluster> = vec![];
+static mut ENEMY_CLUSTERS: Vec<Cluster> = vec![];
+static mut NEXT_CLUSTER_ID: i32 = 0;
 static mut FIGHT_SIM_RESULT: Vec<FightSim> = vec![];
+static mut TARGETS: Vec<(i32, i32)> = vec![];
+static mut TEAM_PLAN: TeamPlan = TeamPlan::const_default();
+static mut ZONE_PLAN: ZonePlan = ZonePlan::const_default();
+static mut OBJECTIVE: Objective = Objective::const_default();
+static mut SCOUTING_TARGET: Option<Vec2> = None;
+static mut PEEK_COVERS: Vec<(i32, Vec2)> = vec![];
+static mut PREVIOUS_BEHAVIOURS: Vec<(i32, &'static str)> = vec![];
 
 static mut PROJECTILES: Vec<Projectile> = vec![];
 
+pub fn get_my_clusters() -> &'static Vec<Cluster> {
+    unsafe { &MY_CLUSTERS }
+}
+
+pub fn get_enemy_clusters() -> &'static Vec<Cluster> {
+    unsafe { &ENEMY_CLUSTERS }
+}
+
 pub fn get_fight_simulations() -> &'static Vec<FightSim> {
     unsafe { &FIGHT_SIM_RESULT }
 }
 
-pub fn book_loot(id: i32) {
-    unsafe { BOOKED_LOOT.push(id) }
+pub fn set_targets(targets: Vec<(i32, i32)>) {
+    unsafe { TARGETS = targets }
 }
 
-pub fn is_loot_booked(id: &i32) -> bool {
-    unsafe { BOOKED_LOOT.contains(&id) }
+pub fn get_target(unit_id: i32) -> Option<&'static Unit> {
+    unsafe { &TARGETS }
+        .iter()
+        .find(|(id, _)| id == &unit_id)
+        .and_then(|(_, target_id)| get_all_enemy_units().iter().find(|e| &e.id == target_id))
+}
+
+pub fn set_team_plan(plan: TeamPlan) {
+    unsafe { TEAM_PLAN = plan }
+}
+
+pub fn get_team_plan() -> &'static TeamPlan {
+    unsafe { &TEAM_PLAN }
+}
+
+pub fn set_zone_plan(plan: ZonePlan) {
+    unsafe { ZONE_PLAN = plan }
+}
+
+pub fn get_zone_plan() -> &'static ZonePlan {
+    unsafe { &ZONE_PLAN }
+}
+
+pub fn set_objective(objective: Objective) {
+    unsafe { OBJECTIVE = objective }
+}
+
+pub fn get_objective() -> &'static Objective {
+    unsafe { &OBJECTIVE }
+}
+
+pub fn get_previous_behaviour(unit_id: i32) -> Option<&'static str> {
+    unsafe { &PREVIOUS_BEHAVIOURS }
+        .iter()
+        .find(|(id, _)| id == &unit_id)
+        .map(|(_, name)| *name)
+}
+
+pub fn set_previous_behaviour(unit_id: i32, name: Option<&'static str>) {
+    unsafe {
+        PREVIOUS_BEHAVIOURS.retain(|(id, _)| id != &unit_id);
+        if let Some(name) = name {
+            PREVIOUS_BEHAVIOURS.push((unit_id, name));
+        }
+    }
+}
+
+pub fn set_loot_routes(routes: Vec<(i32, Vec<Loot>)>) {
+    unsafe { LOOT_ROUTES = routes }
+}
+
+pub fn get_loot_route(unit_id: i32) -> &'static [Loot] {
+    unsafe { &LOOT_ROUTES }
+        .iter()
+        .find(|(id, _)| *id == unit_id)
+        .map_or(&[], |(_, route)| route.as_slice())
+}
+
+pub fn is_allocated_to_other(loot_id: i32, unit_id: i32) -> bool {
+    unsafe { &LOOT_ROUTES }
+        .iter()
+        .any(|(id, route)| *id != unit_id && route.iter().any(|l| l.id == loot_id))
 }
 
 pub fn get_constants() -> &'static Constants {
@@ -47,19 +148,35 @@ pub fn get_all_enemy_units() -> &'static Vec<Unit> {
     unsafe { &UNITS }
 }
 
+pub fn get_enemy_beliefs() -> &'static Vec<EnemyBelief> {
+    unsafe { &ENEMY_BELIEFS }
+}
+
+pub fn get_enemy_inventory(unit_id: i32) -> Option<&'static EnemyInventory> {
+    unsafe { &ENEMY_INVENTORIES }
+        .iter()
+        .find(|i| i.unit_id == unit_id)
+}
+
+pub fn get_unexplained_sounds() -> &'static Vec<SoundInference> {
+    unsafe { &UNEXPLAINED_SOUNDS }
+}
+
 pub fn get_loot() -> &'static Vec<Loot> {
     unsafe { &LOOT }
 }
 
 pub unsafe fn remove_loot(id_to_remove: i32) {
-    let mut res = Vec::new();
-    for x in &LOOT_TO_TICK {
-        if x.1.id != id_to_remove {
-            res.push((x.clone()));
-        }
-    }
-    LOOT_TO_TICK = res;
-    unsafe { LOOT = LOOT_TO_TICK.iter().map(|e| e.1.clone()).collect_vec() };
+    LOOT_MEMORY.retain(|l| l.loot.id != id_to_remove);
+    LOOT.retain(|l| l.id != id_to_remove);
+}
+
+// chance the loot is still where we saw it
+pub fn get_loot_confidence(loot_id: i32) -> f64 {
+    unsafe { &LOOT_MEMORY }
+        .iter()
+        .find(|l| l.loot.id == loot_id)
+        .map_or(0.0, |l| l.confidence)
 }
 
 pub fn get_projectiles() -> &'static Vec<Projectile> {
@@ -68,6 +185,39 @@ pub fn get_projectiles() -> &'static Vec<Projectile> {
 
 pub fn set_constants(constants: Constants) {
     unsafe { CONSTANTS = constants }
+    unsafe { COVER_MAP = build_cover_map() }
+}
+
+pub fn get_cover_map() -> &'static CoverMap {
+    unsafe { &COVER_MAP }
+}
+
+pub fn get_scouting_target() -> Option<Vec2> {
+    unsafe { SCOUTING_TARGET }
+}
+
+pub fn set_scouting_target(target: Option<Vec2>) {
+    unsafe { SCOUTING_TARGET = target }
+}
+
+pub fn get_peek_cover(unit_id: i32) -> Option<Vec2> {
+    unsafe { &PEEK_COVERS }
+        .iter()
+        .find(|(id, _)| *id == unit_id)
+        .map(|(_, cover)| *cover)
+}
+
+pub fn set_peek_cover(unit_id: i32, cover: Option<Vec2>) {
+    unsafe {
+        PEEK_COVERS.retain(|(id, _)| *id != unit_id);
+        if let Some(cover) = cover {
+            PEEK_COVERS.push((unit_id, cover));
+        }
+    }
+}
+
+pub fn get_exploration_grid() -> &'static ExplorationGrid {
+    unsafe { &EXPLORATION_GRID }
 }
 
 pub fn get_obstacles(unit_id: i32) -> Vec<Obstacle> {
@@ -79,123 +229,96 @@ pub fn get_obstacles(unit_id: i32) -> Vec<Obstacle> {
 }
 
 pub fn update_game(game: Game, debug_interface: &mut Option<&mut DebugInterface>) {
-    unsafe { BOOKED_LOOT.clear() }
     let constants = get_constants();
 
     set_nearest_obstacles(&game, constants);
-    update_units(&game, debug_interface);
+    // what we knew on the previous tick, the inventories learn from what changed since
+    let previous_projectiles = get_projectiles().clone();
+    let previous_loot = get_loot().clone();
+    update_units(
+        &game,
+        &previous_projectiles,
+        &previous_loot,
+        debug_interface,
+    );
     update_loot(&game);
     update_projectiles(&game);
+    unsafe { EXPLORATION_GRID.update(&game) }
 
     unsafe { GAME = game }
 
+    update_clusters();
     unsafe { FIGHT_SIM_RESULT = create_fight_simulations(debug_interface) }
 }
 
-fn update_units(game: &Game, debug_interface: &mut Option<&mut DebugInterface>) {
-    let unit_ttl = 50;
-    let mut units_hashmap = HashMap::new();
-    for x in game.enemy_units() {
-        units_hashmap.insert(x.id, (unit_ttl, x.clone()));
+fn update_units(
+    game: &Game,
+    previous_projectiles: &[Projectile],
+    previous_loot: &[Loot],
+    debug_interface: &mut Option<&mut DebugInterface>,
+) {
+    let mut observations = projectile_observations(game, unsafe { &FUSED_PROJECTILES });
+    unsafe { FUSED_PROJECTILES = game.projectiles.iter().map(|p| p.id).collect_vec() };
+
+    let sounds = interpret_all(game);
+    observations.extend(
+        sound_observations(game, &sounds)
+            .into_iter()
+            .filter(|o| !inside_vision(game, &o.position)),
+    );
+    unsafe {
+        UNEXPLAINED_SOUNDS.retain(|s| game.current_tick - s.tick < SOUND_MEMORY_TICKS);
+        UNEXPLAINED_SOUNDS.extend(sounds.into_iter().filter(|s| is_unexplained(game, s)));
     }
-    for x in unsafe { &UNIT_TO_TICK } {
-        if !units_hashmap.contains_key(&x.1.id) && !inside_vision(game, &x.1.position) {
-            if x.0 - 1 > 0 {
-                units_hashmap.insert(x.1.id, (x.0 - 1, x.1.clone()));
-            }
+
+    let mut beliefs = update_beliefs(unsafe { &ENEMY_BELIEFS }, game, observations);
+    let inventories = update_inventories(
+        unsafe { &ENEMY_INVENTORIES },
+        game,
+        &beliefs,
+        previous_projectiles,
+        previous_loot,
+    );
+    for belief in beliefs
+        .iter_mut()
+        .filter(|b| b.last_seen_tick != game.current_tick)
+    {
+        if let Some(inventory) = inventories.iter().find(|i| i.unit_id == belief.unit.id) {
+            inventory.apply(&mut belief.unit);
         }
     }
-    //deduce shooter position by projectiles
-    for projectile in &game.projectiles {
-        let w_id = projectile.weapon_type_index as usize;
-        let w = &get_constants().weapons[w_id];
-        let fly_time = w.projectile_life_time - projectile.life_time;
-
-        let unit_pos = projectile.position.clone() - (projectile.velocity.clone() * fly_time);
-        let ticks = (fly_time * &get_constants().ticks_per_second).ceil() as i32;
-
-        if projectile.shooter_player_id != game.my_id
-            && !units_hashmap.contains_key(&projectile.shooter_id)
-            && !inside_vision(game, &unit_pos)
-        {
-            // if let Some(d) = debug_interface.as_mut() {
-            //     d.add_circle(unit_pos.clone(), 15.0, RED.clone());
-            //     d.add_circle(projectile.position.clone(), 5.0, BLUE.clone());
-            // }
-
-            let imaginary_unit = Unit {
-                id: projectile.shooter_id,
-                position: unit_pos,
-                direction: projectile.velocity.clone(),
-                weapon: Some(w_id as i32),
-                health: get_constants().unit_health.clone(),
-                ammo: Vec::from([100, 100, 100, 100]),
-                ..Unit::default()
-            };
-            units_hashmap.insert(projectile.shooter_id, (unit_ttl - ticks, imaginary_unit));
+    unsafe { ENEMY_INVENTORIES = inventories };
+    if let Some(debug) = debug_interface.as_mut() {
+        for belief in beliefs.iter().filter(|b| b.uncertainty > 0.0) {
+            debug.add_ring(belief.unit.position, belief.uncertainty, 0.1, RED);
         }
     }
+    unsafe { UNITS = beliefs.iter().map(|b| b.unit.clone()).collect_vec() };
+    unsafe { ENEMY_BELIEFS = beliefs };
+}
 
-    // Steps 0.05 10
-    // Wand 0.1 30
-    // Staff 0.1 40
-    // Bow 0.1 20
-    // WandHit 0.15 40
-    // StaffHit 0.15 40
-    // BowHit 0.15 40
-    // add units from sounds
-    for sound in &get_game().sounds {
-        if inside_vision(game, &sound.position) { continue; };
-        let nearest_unit_distance = get_all_enemy_units().iter().map(|e| e.position).chain(get_game().my_units().iter().map(|e| e.position))
-            .map(|e| e.distance(&sound.position)).min_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap_or(f64::MAX);
-        if nearest_unit_distance < 5.0 { continue; };
-        let id = get_all_enemy_units().iter().map(|e| e.id).min().unwrap_or(0) - 1;
-        if sound.type_index == 0 {
-            let imaginary_unit = Unit {
-                id,
-                position: sound.position.clone(),
-                weapon: Some(2),
-                health: get_constants().unit_health.clone(),
-                ammo: Vec::from([100, 100, 100, 100]),
-                ..Unit::default()
-            };
-            units_hashmap.insert(id, (unit_ttl, imaginary_unit));
-            continue;
-        }
-        if sound.type_index > 0 && sound.type_index < 4 {
-            let weapon = sound.type_index - 1;
-            let imaginary_unit = Unit {
-                id,
-                position: sound.position.clone(),
-                weapon: None,
-                health: get_constants().unit_health.clone(),
-                ammo: Vec::from([100, 100, 100, 100]),
-                ..Unit::default()
-            };
-            units_hashmap.insert(id, (unit_ttl, imaginary_unit));
-        }
+fn update_clusters() {
+    let my_units = get_game()
+        .my_units()
+        .into_iter()
+        .filter(|u| u.remaining_spawn_time.is_none())
+        .collect_vec();
+    let enemies = get_all_enemy_units()
+        .iter()
+        .filter(|e| e.remaining_spawn_time.is_none())
+        .collect_vec();
+    // Our units never share a cluster with the enemies: a fight simulation is one of our
+    // clusters against one enemy cluster, and a group mixing both sides would fight itself.
+    unsafe {
+        MY_CLUSTERS = create_clusters(&my_units, &MY_CLUSTERS, &mut NEXT_CLUSTER_ID);
+        ENEMY_CLUSTERS = create_clusters(&enemies, &ENEMY_CLUSTERS, &mut NEXT_CLUSTER_ID);
     }
-
-    unsafe { UNIT_TO_TICK = units_hashmap.iter().map(|e| e.1.clone()).collect_vec() };
-    unsafe { UNITS = units_hashmap.iter().map(|e| e.1.1.clone()).collect_vec() };
 }
 
 fn update_loot(game: &Game) {
-    let loot_ttl = 150;
-    let mut loot_hashmap = HashMap::new();
-    for x in &game.loot {
-        loot_hashmap.insert(x.id, (loot_ttl, x.clone()));
-    }
-    for x in unsafe { &LOOT_TO_TICK } {
-        if !loot_hashmap.contains_key(&x.1.id) && !inside_vision(game, &x.1.position) {
-            if x.0 - 1 > 0 {
-                loot_hashmap.insert(x.1.id, (x.0 - 1, x.1.clone()));
-            }
-        }
-    }
-    unsafe { LOOT_TO_TICK = loot_hashmap.iter().map(|e| e.1.clone()).collect_vec() };
-    unsafe { LOOT = loot_hashmap.iter().map(|e| e.1.1.clone()).collect_vec() };
+    let memory = update_loot_memory(unsafe { &LOOT_MEMORY }, game, get_enemy_beliefs());
+    unsafe { LOOT = memory.iter().map(|l| l.loot.clone()).collect_vec() };
+    unsafe { LOOT_MEMORY = memory };
 }
 
 fn update_projectiles(game: &Game) {
@@ -247,8 +370,9 @@ fn update_projectiles(game: &Game) {
     unsafe { PROJECTILES = projectiles_map.iter().map(|e| e.1.clone()).collect_vec() };
 }
 
-fn inside_vision(game: &Game, x: &Vec2) -> bool {
-    let is_in_vision_sector = game.my_units()
+pub fn inside_vision(game: &Game, x: &Vec2) -> bool {
+    let is_in_vision_sector = game
+        .my_units()
         .iter()
         .filter(|e| e.position.distance(x) <= get_constants().view_distance)
         .any(|u| {
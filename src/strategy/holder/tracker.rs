@@ -0,0 +1,208 @@
+use super::inside_vision;
+use crate::model::{Game, Unit, Vec2};
+use crate::strategy::holder::get_constants;
+use itertools::Itertools;
+
+// velocity of unseen enemies fades out, they don't keep running straight forever
+const VELOCITY_DAMPING: f64 = 0.9;
+// beliefs this wide are useless, we forget such enemies
+const MAX_UNCERTAINTY: f64 = 30.0;
+// we would have seen an enemy this well localized inside our vision
+const VISIBLE_UNCERTAINTY: f64 = 3.0;
+const PROJECTILE_UNCERTAINTY: f64 = 2.0;
+// in standard deviations
+const ASSOCIATION_GATE: f64 = 3.0;
+const MIN_UNCERTAINTY: f64 = 0.1;
+// anonymous observations vaguer than that only refine beliefs, they don't start new ones
+const NEW_BELIEF_MAX_UNCERTAINTY: f64 = 10.0;
+
+// Gaussian belief about the position of an enemy, the unit keeps the last known state
+// with the position replaced by the belief mean
+#[derive(Clone, Debug)]
+pub struct EnemyBelief {
+    pub unit: Unit,
+    // standard deviation of the position
+    pub uncertainty: f64,
+    pub last_seen_tick: i32,
+}
+
+// where the enemy was noticed without seeing it
+pub struct Observation {
+    // id of the enemy if we know who it was
+    pub unit_id: Option<i32>,
+    pub player_id: Option<i32>,
+    pub position: Vec2,
+    pub uncertainty: f64,
+    pub weapon: Option<i32>,
+}
+
+impl EnemyBelief {
+    fn seen(unit: &Unit, tick: i32) -> Self {
+        EnemyBelief {
+            unit: unit.clone(),
+            uncertainty: 0.0,
+            last_seen_tick: tick,
+        }
+    }
+
+    fn contains(&self, p: &Vec2, uncertainty: f64) -> bool {
+        self.unit.position.distance(p)
+            <= ASSOCIATION_GATE * (self.uncertainty + uncertainty) + get_constants().unit_radius
+    }
+
+    fn spread(&mut self) {
+        let constants = get_constants();
+        self.unit.position = self.unit.position + self.unit.velocity / constants.ticks_per_second;
+        self.unit.velocity = self.unit.velocity * VELOCITY_DAMPING;
+        self.uncertainty += constants.max_unit_forward_speed / constants.ticks_per_second;
+    }
+
+    // product of the two gaussians
+    fn fuse(&mut self, observation: &Observation) {
+        self.fuse_position(&observation.position, observation.uncertainty);
+        if observation.weapon.is_some() {
+            self.unit.weapon = observation.weapon;
+        }
+    }
+
+    fn fuse_position(&mut self, position: &Vec2, uncertainty: f64) {
+        let w1 = 1.0 / self.uncertainty.max(MIN_UNCERTAINTY).powi(2);
+        let w2 = 1.0 / uncertainty.max(MIN_UNCERTAINTY).powi(2);
+        self.unit.position = (self.unit.position * w1 + *position * w2) / (w1 + w2);
+        self.uncertainty = (1.0 / (w1 + w2)).sqrt();
+    }
+}
+
+pub fn update_beliefs(
+    previous: &[EnemyBelief],
+    game: &Game,
+    observations: Vec<Observation>,
+) -> Vec<EnemyBelief> {
+    let seen = game.enemy_units();
+    let mut beliefs = seen
+        .iter()
+        .map(|u| EnemyBelief::seen(u, game.current_tick))
+        .collect_vec();
+
+    for belief in previous {
+        if seen.iter().any(|u| u.id == belief.unit.id) {
+            continue;
+        }
+        // enemies we don't know ids of are merged into whoever we see inside their area
+        if belief.unit.id < 0 && seen.iter().any(|u| belief.contains(&u.position, 0.0)) {
+            continue;
+        }
+        let mut belief = belief.clone();
+        belief.spread();
+        let should_have_been_seen =
+            belief.uncertainty < VISIBLE_UNCERTAINTY && inside_vision(game, &belief.unit.position);
+        if belief.uncertainty < MAX_UNCERTAINTY && !should_have_been_seen {
+            beliefs.push(belief);
+        }
+    }
+
+    for observation in observations {
+        let known = match observation.unit_id {
+            Some(id) => beliefs.iter().position(|b| b.unit.id == id),
+            None => beliefs
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| b.contains(&observation.position, observation.uncertainty))
+                .min_by(|(_, a), (_, b)| {
+                    let da =
+                        a.unit.position.distance(&observation.position) / a.uncertainty.max(1.0);
+                    let db =
+                        b.unit.position.distance(&observation.position) / b.uncertainty.max(1.0);
+                    da.partial_cmp(&db).unwrap()
+                })
+                .map(|(i, _)| i),
+        };
+        match known {
+            // already explained by the enemy we see
+            Some(i) if beliefs[i].last_seen_tick == game.current_tick => {}
+            Some(i) => beliefs[i].fuse(&observation),
+            None if observation.unit_id.is_none()
+                && observation.uncertainty > NEW_BELIEF_MAX_UNCERTAINTY => {}
+            None => {
+                let id = observation.unit_id.unwrap_or_else(|| {
+                    beliefs
+                        .iter()
+                        .map(|b| b.unit.id)
+                        .filter(|id| *id < 0)
+                        .min()
+                        .unwrap_or(0)
+                        - 1
+                });
+                beliefs.push(EnemyBelief {
+                    unit: Unit {
+                        id,
+                        player_id: observation.player_id.unwrap_or(0),
+                        position: observation.position,
+                        weapon: observation.weapon,
+                        // the rest is filled from the inventory estimate
+                        ..Unit::default()
+                    },
+                    uncertainty: observation.uncertainty,
+                    last_seen_tick: -1,
+                });
+            }
+        }
+    }
+    merge_anonymous(&mut beliefs);
+    beliefs
+}
+
+// an enemy we don't know the id of is most likely one we already track nearby,
+// preferably a known one, otherwise the older anonymous one
+fn merge_anonymous(beliefs: &mut Vec<EnemyBelief>) {
+    let mut i = 0;
+    while i < beliefs.len() {
+        let anonymous = &beliefs[i];
+        let into = if anonymous.unit.id < 0 {
+            beliefs
+                .iter()
+                .enumerate()
+                .filter(|(j, b)| *j != i && b.unit.id > anonymous.unit.id)
+                .filter(|(_, b)| b.contains(&anonymous.unit.position, anonymous.uncertainty))
+                .max_by_key(|(_, b)| b.unit.id)
+                .map(|(j, _)| j)
+        } else {
+            None
+        };
+        match into {
+            Some(j) => {
+                let anonymous = beliefs.remove(i);
+                let j = if j > i { j - 1 } else { j };
+                // enemies we see stay where we see them
+                if beliefs[j].uncertainty > 0.0 {
+                    beliefs[j].fuse_position(&anonymous.unit.position, anonymous.uncertainty);
+                }
+            }
+            None => i += 1,
+        }
+    }
+}
+
+// shooters of projectiles we see while they stay out of sight, every projectile tells
+// about its shooter once, fusing it again each tick would shrink the belief for nothing
+pub fn projectile_observations(game: &Game, fused: &[i32]) -> Vec<Observation> {
+    game.projectiles
+        .iter()
+        .filter(|p| p.shooter_player_id != game.my_id)
+        .filter(|p| !fused.contains(&p.id))
+        .filter(|p| game.enemy_units().iter().all(|u| u.id != p.shooter_id))
+        .map(|p| {
+            let weapon = &get_constants().weapons[p.weapon_type_index as usize];
+            let fly_time = weapon.projectile_life_time - p.life_time;
+            Observation {
+                unit_id: Some(p.shooter_id),
+                player_id: Some(p.shooter_player_id),
+                position: p.position - p.velocity * fly_time,
+                uncertainty: PROJECTILE_UNCERTAINTY
+                    + fly_time * get_constants().max_unit_forward_speed,
+                weapon: Some(p.weapon_type_index),
+            }
+        })
+        .filter(|o| !inside_vision(game, &o.position))
+        .collect_vec()
+}
@@ -0,0 +1,175 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::TRANSPARENT_TEAL;
+use crate::model::{Loot, Unit};
+use crate::strategy::holder::{get_game, get_loot, get_team_plan, set_loot_routes};
+use crate::strategy::loot::valuation::after_pickup;
+use crate::strategy::loot::{is_contested, loot_score};
+use crate::strategy::team::Role;
+use itertools::Itertools;
+
+// a unit left without loot costs more than any real pair
+const NO_LOOT_COST: f64 = 1e6;
+const INFEASIBLE_COST: f64 = 1e9;
+const MAX_ROUTE_LENGTH: usize = 3;
+
+// Every unit gets the loot which minimizes the total travel minus value over the team,
+// then extends its route with items nobody else takes.
+pub fn allocate_loot(debug_interface: &mut Option<&mut DebugInterface>) {
+    let plan = get_team_plan();
+    let units = get_game()
+        .my_units()
+        .into_iter()
+        .filter(|u| plan.role(u.id) != Role::Cover)
+        .sorted_by_key(|u| u.id)
+        .collect_vec();
+    let loot = get_loot()
+        .iter()
+        .filter(|l| units.iter().any(|u| !is_contested(l, u)))
+        .collect_vec();
+    if units.is_empty() || loot.is_empty() {
+        set_loot_routes(vec![]);
+        return;
+    }
+
+    let columns = loot.len().max(units.len());
+    let cost = units
+        .iter()
+        .map(|u| {
+            (0..columns)
+                .map(|j| match loot.get(j) {
+                    None => NO_LOOT_COST,
+                    Some(l) => pickup_cost(u, l).unwrap_or(INFEASIBLE_COST),
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+    let assignment = hungarian(&cost);
+
+    let mut taken = vec![false; loot.len()];
+    let mut routes = vec![];
+    for (i, unit) in units.iter().enumerate() {
+        match assignment[i] {
+            Some(j) if j < loot.len() && cost[i][j] < NO_LOOT_COST => {
+                taken[j] = true;
+                routes.push((unit.id, vec![loot[j].clone()]));
+            }
+            _ => routes.push((unit.id, vec![])),
+        }
+    }
+    for (i, unit) in units.iter().enumerate() {
+        extend_route(unit, &mut routes[i].1, &loot, &mut taken);
+    }
+
+    if let Some(debug) = debug_interface.as_mut() {
+        for (unit, (_, route)) in units.iter().zip(routes.iter()) {
+            if !route.is_empty() {
+                let mut vertices = vec![unit.position];
+                vertices.extend(route.iter().map(|l| l.position));
+                debug.add_poly_line(vertices, 0.2, TRANSPARENT_TEAL);
+            }
+        }
+    }
+    set_loot_routes(routes);
+}
+
+// travel minus value, None for loot the unit doesn't need or can't take
+fn pickup_cost(unit: &Unit, loot: &Loot) -> Option<f64> {
+    if is_contested(loot, unit) {
+        return None;
+    }
+    loot_score(unit, loot).map(|score| unit.position.distance(&loot.position) - score)
+}
+
+// keeps adding free items worth the detour, counting what the unit picked up before
+fn extend_route(unit: &Unit, route: &mut Vec<Loot>, loot: &[&Loot], taken: &mut [bool]) {
+    let mut current = match route.last() {
+        None => return,
+        Some(first) => after_pickup(unit, &first.item),
+    };
+    while route.len() < MAX_ROUTE_LENGTH {
+        let from = route.last().unwrap().position;
+        current.position = from;
+        let next = loot
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| !taken[*j])
+            .filter_map(|(j, l)| {
+                loot_score(&current, l).map(|score| (j, score - from.distance(&l.position)))
+            })
+            .filter(|(_, gain)| *gain > 0.0)
+            .max_by(|(_, g1), (_, g2)| g1.partial_cmp(g2).unwrap());
+        match next {
+            None => return,
+            Some((j, _)) => {
+                taken[j] = true;
+                current = after_pickup(&current, &loot[j].item);
+                route.push(loot[j].clone());
+            }
+        }
+    }
+}
+
+// Minimal cost assignment of every row to a distinct column, rows <= columns.
+// Potentials based Hungarian algorithm, O(rows^2 * columns).
+fn hungarian(cost: &[Vec<f64>]) -> Vec<Option<usize>> {
+    let n = cost.len();
+    let m = cost.first().map_or(0, |row| row.len());
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; m + 1];
+    // row assigned to the column, 1-based, 0 is none
+    let mut p = vec![0; m + 1];
+    let mut way = vec![0; m + 1];
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut min_v = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let current = cost[i0 - 1][j - 1] - u[i0] - v[j];
+                if current < min_v[j] {
+                    min_v[j] = current;
+                    way[j] = j0;
+                }
+                if min_v[j] < delta {
+                    delta = min_v[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_v[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+    let mut assignment = vec![None; n];
+    for j in 1..=m {
+        if p[j] != 0 {
+            assignment[p[j] - 1] = Some(j - 1);
+        }
+    }
+    assignment
+}
@@ -1,100 +1,68 @@
-use crate::model::{Item, Loot, Unit, Vec2};
+pub mod allocation;
+pub mod valuation;
+
+use crate::model::{Loot, Unit, Vec2};
 use crate::strategy::behaviour::behaviour::{my_units_collision_score, my_units_magnet_score};
-use crate::strategy::holder::{get_all_enemy_units, get_constants, get_game, is_loot_booked};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_enemy_beliefs, get_game, get_loot_confidence,
+    get_loot_route, is_allocated_to_other,
+};
+use crate::strategy::loot::valuation::loot_value;
 use itertools::Itertools;
 use libc::clone;
 use std::any::Any;
 
+// an enemy has to be this much closer to the loot to get it before us
+const CONTEST_MARGIN: f64 = 2.0;
+
 pub fn best_loot(unit: &Unit, loots: &Vec<Loot>, intersecting: bool) -> Option<Loot> {
     let constants = get_constants();
-    let current_weapon = unit.weapon;
-    let ammo = unit.ammo.clone();
+    if !intersecting {
+        // the next item of the route the team planned for the unit
+        return get_loot_route(unit.id)
+            .iter()
+            .filter(|l| unit.position.distance(&l.position) >= constants.unit_radius)
+            .find(|l| loots.iter().any(|e| e.id == l.id))
+            .cloned();
+    }
     loots
         .iter()
-        .filter(|l| !is_loot_booked(&l.id))
-        .filter(|l| is_inside_zone(l))
-        .filter(|l| is_loot_needed(l, unit))
-        .filter(|l| {
-            get_all_enemy_units()
-                .iter()
-                .find(|e| {
-                    get_game().current_tick < 6000
-                        && e.position.distance(&l.position) + get_constants().unit_radius
-                            < e.firing_distance()
-                })
-                .is_none()
-        })
-        .filter(|l| {
-            if !intersecting {
-                unit.position.distance(&l.position) >= constants.unit_radius
+        .filter(|l| !is_allocated_to_other(l.id, unit.id))
+        .filter(|l| unit.position.distance(&l.position) < constants.unit_radius)
+        .filter_map(|l| loot_score(unit, l).map(|score| (score, l)))
+        .max_by(|(score1, _), (score2, _)| score1.partial_cmp(score2).unwrap())
+        .map(|e| e.1.clone())
+}
+
+// how much the unit wants the loot not counting the way there, None if it doesn't want it
+pub fn loot_score(unit: &Unit, l: &Loot) -> Option<f64> {
+    if !is_inside_zone(l) {
+        return None;
+    }
+    let under_fire = get_all_enemy_units().iter().any(|e| {
+        get_game().current_tick < 6000
+            && e.position.distance(&l.position) + get_constants().unit_radius < e.firing_distance()
+    });
+    if under_fire {
+        return None;
+    }
+    let value = loot_value(&l.item, unit, &unit.my_other_units());
+    if value <= 0.0 {
+        return None;
+    }
+    let enemy_score = get_all_enemy_units()
+        .iter()
+        .map(|e| {
+            let distance = e.position.distance(&l.position);
+            if distance <= e.firing_distance() {
+                e.firing_distance() - distance
             } else {
-                unit.position.distance(&l.position) < constants.unit_radius
+                0.0
             }
         })
-        .map(|l| {
-            let score: i32 = match l.item {
-                Item::Weapon { type_index } => match current_weapon {
-                    None => (type_index + 1) * 10,
-                    Some(current_weapon) => {
-                        if unit.ammo[type_index as usize] != 0
-                            && (current_weapon < type_index
-                                || (unit.ammo[current_weapon as usize] == 0))
-                        {
-                            (type_index + 1) * 10
-                        } else {
-                            0
-                        }
-                    }
-                },
-                Item::ShieldPotions { amount } => {
-                    if unit.shield_potions < constants.max_shield_potions_in_inventory {
-                        (10.0 / (unit.health / constants.unit_health)).ceil() as i32
-                    } else {
-                        0
-                    }
-                }
-                Item::Ammo {
-                    weapon_type_index,
-                    amount,
-                } => {
-                    let percent_of_max_ammo = ammo[weapon_type_index as usize] as f64
-                        / constants.weapons[weapon_type_index as usize].max_inventory_ammo as f64;
-                    if percent_of_max_ammo == 1.0 {
-                        0
-                    } else if percent_of_max_ammo == 0.0 {
-                        (weapon_type_index + 1) * 5
-                    } else {
-                        ((weapon_type_index + 1) as f64 * 5.0 / percent_of_max_ammo).ceil() as i32
-                    }
-                }
-            };
-            // println!("{}, {}, {}", match l.item {
-            //     Item::Weapon { .. } => { "w" }
-            //     Item::ShieldPotions { .. } => { "shield" }
-            //     Item::Ammo { .. } => { "ammo" }
-            // }, score, -l.position.distance(&unit.position) + my_units_magnet_score(&l.position, unit));
-            let enemy_score = get_all_enemy_units()
-                .iter()
-                .map(|e| {
-                    let distance = e.position.distance(&l.position);
-                    if distance <= e.firing_distance() {
-                        e.firing_distance() - distance
-                    } else {
-                        0.0
-                    }
-                })
-                .sum::<f64>()
-                * 0.1;
-
-            (
-                score.clone() as f64 + enemy_score
-                    - l.position.distance(&unit.position)
-                    - my_units_magnet_score(&l.position, unit),
-                l,
-            )
-        })
-        .max_by(|(score1, _), (score2, _)| score1.partial_cmp(score2).unwrap())
-        .map(|e| e.1.clone())
+        .sum::<f64>()
+        * 0.1;
+    Some(value * get_loot_confidence(l.id) + enemy_score - my_units_magnet_score(&l.position, unit))
 }
 
 fn is_inside_zone(loot: &Loot) -> bool {
@@ -106,44 +74,13 @@ fn is_inside_zone(loot: &Loot) -> bool {
         <= game.zone.current_radius - zone_speed * (distance / max_speed)
 }
 
-fn is_loot_needed(l: &Loot, unit: &Unit) -> bool {
-    let constants = get_constants();
-    let current_weapon = unit.weapon;
-    let ammo = &unit.ammo;
-
-    match l.item {
-        Item::Weapon { type_index } => match current_weapon {
-            None => true,
-            Some(current_weapon) => {
-                if unit.ammo[type_index as usize] != 0
-                    && (current_weapon < type_index || (unit.ammo[current_weapon as usize] == 0))
-                {
-                    true
-                } else {
-                    false
-                }
-            }
-        },
-        Item::ShieldPotions { amount } => {
-            if unit.shield_potions < constants.max_shield_potions_in_inventory {
-                true
-            } else {
-                false
-            }
-        }
-        Item::Ammo {
-            weapon_type_index,
-            amount,
-        } => {
-            let percent_of_max_ammo = ammo[weapon_type_index as usize] as f64
-                / constants.weapons[weapon_type_index as usize].max_inventory_ammo as f64;
-            if percent_of_max_ammo == 1.0 {
-                false
-            } else if percent_of_max_ammo == 0.0 {
-                true
-            } else {
-                true
-            }
-        }
-    }
+// some enemy surely reaches the loot before us
+pub fn is_contested(loot: &Loot, unit: &Unit) -> bool {
+    let my_distance = unit.position.distance(&loot.position);
+    get_enemy_beliefs()
+        .iter()
+        .filter(|b| b.unit.remaining_spawn_time.is_none())
+        .any(|b| {
+            b.unit.position.distance(&loot.position) + b.uncertainty + CONTEST_MARGIN < my_distance
+        })
 }
@@ -0,0 +1,150 @@
+use crate::model::{Item, Unit, WeaponProperties};
+use crate::strategy::holder::get_constants;
+
+// a typical fight, ammo for more shots than that doesn't make us stronger right now
+const FIGHT_SECONDS: f64 = 10.0;
+// distance most fights happen at
+const TYPICAL_FIGHT_DISTANCE: f64 = 15.0;
+// not every potion gets drunk before the unit dies, every next one is worth less
+const POTION_USABILITY: f64 = 0.7;
+// ammo for a weapon we don't hold is only worth something once we find the weapon
+const OPTION_VALUE: f64 = 0.3;
+// bonus for a range nobody else in the team covers
+const DIVERSITY_BONUS: f64 = 0.2;
+// keeps defensive items worth something to units without a weapon
+const UNARMED_OFFENSE: f64 = 1.0;
+// strength gain of a reference unit getting from nothing to the best loadout is worth this
+const VALUE_SCALE: f64 = 30.0;
+
+#[derive(Clone, Debug)]
+struct Loadout {
+    weapon: Option<i32>,
+    ammo: Vec<i32>,
+    shield_potions: i32,
+    health: f64,
+    shield: f64,
+}
+
+impl Loadout {
+    fn of(unit: &Unit) -> Self {
+        Loadout {
+            weapon: unit.weapon,
+            ammo: unit.ammo.clone(),
+            shield_potions: unit.shield_potions,
+            health: unit.health,
+            shield: unit.shield,
+        }
+    }
+
+    fn with(&self, item: &Item) -> Self {
+        let constants = get_constants();
+        let mut loadout = self.clone();
+        match item {
+            Item::Weapon { type_index } => loadout.weapon = Some(*type_index),
+            Item::ShieldPotions { amount } => {
+                loadout.shield_potions =
+                    (loadout.shield_potions + amount).min(constants.max_shield_potions_in_inventory)
+            }
+            Item::Ammo {
+                weapon_type_index,
+                amount,
+            } => {
+                let max = constants.weapons[*weapon_type_index as usize].max_inventory_ammo;
+                if let Some(ammo) = loadout.ammo.get_mut(*weapon_type_index as usize) {
+                    *ammo = (*ammo + amount).min(max);
+                }
+            }
+        }
+        loadout
+    }
+
+    // damage per second the unit keeps up during a fight
+    fn offense(&self) -> f64 {
+        match self.weapon {
+            None => 0.0,
+            Some(w) => weapon_strength(
+                &get_constants().weapons[w as usize],
+                self.ammo.get(w as usize).copied().unwrap_or(0),
+                TYPICAL_FIGHT_DISTANCE,
+            ),
+        }
+    }
+
+    // health the unit can lose before dying, counting potions it will likely drink
+    fn effective_health(&self) -> f64 {
+        let constants = get_constants();
+        let potions = (0..self.shield_potions)
+            .map(|i| constants.shield_per_potion * POTION_USABILITY.powi(i + 1))
+            .sum::<f64>();
+        self.health + self.shield + potions
+    }
+
+    // Lanchester-like, a fight lasts as long as we survive and we deal damage all that time
+    fn strength(&self) -> f64 {
+        (self.offense() + UNARMED_OFFENSE) * self.effective_health()
+    }
+}
+
+// damage per second the weapon keeps up in a fight at the distance with that much ammo
+pub fn weapon_strength(weapon: &WeaponProperties, ammo: i32, distance: f64) -> f64 {
+    let constants = get_constants();
+    let distance = distance.min(weapon.firing_distance());
+    let spread_width = 2.0 * distance * (weapon.spread.to_radians() / 2.0).tan();
+    let hit_probability = (2.0 * constants.unit_radius / spread_width).min(1.0);
+    let shots_needed = weapon.rounds_per_second * FIGHT_SECONDS;
+    let sustain = (ammo as f64 / shots_needed).min(1.0);
+    let longest_range = constants
+        .weapons
+        .iter()
+        .map(|w| w.firing_distance())
+        .fold(f64::MIN, f64::max);
+    let range = (weapon.firing_distance() / longest_range).sqrt();
+    weapon.projectile_damage * weapon.rounds_per_second * hit_probability * sustain * range
+}
+
+// the unit as it will be after picking the item up
+pub fn after_pickup(unit: &Unit, item: &Item) -> Unit {
+    let loadout = Loadout::of(unit).with(item);
+    Unit {
+        weapon: loadout.weapon,
+        ammo: loadout.ammo,
+        shield_potions: loadout.shield_potions,
+        ..unit.clone()
+    }
+}
+
+// strength of a full health unit with the best weapon and full ammo
+fn reference_strength() -> f64 {
+    let constants = get_constants();
+    let best_offense = constants
+        .weapons
+        .iter()
+        .map(|w| weapon_strength(w, w.max_inventory_ammo, TYPICAL_FIGHT_DISTANCE))
+        .fold(0.0, f64::max);
+    (best_offense * (constants.unit_health + constants.max_shield)).max(f64::EPSILON)
+}
+
+// expected combat strength gain from picking the item up, 0 for useless items
+pub fn loot_value(item: &Item, unit: &Unit, teammates: &[&Unit]) -> f64 {
+    let before = Loadout::of(unit);
+    let gain = match item {
+        Item::Ammo {
+            weapon_type_index, ..
+        } if before.weapon != Some(*weapon_type_index) => {
+            // as if we already held the weapon
+            let holding = Loadout {
+                weapon: Some(*weapon_type_index),
+                ..before.clone()
+            };
+            (holding.with(item).strength() - holding.strength()) * OPTION_VALUE
+        }
+        _ => before.with(item).strength() - before.strength(),
+    };
+    let diversity = match item {
+        Item::Weapon { type_index } if teammates.iter().all(|t| t.weapon != Some(*type_index)) => {
+            1.0 + DIVERSITY_BONUS
+        }
+        _ => 1.0,
+    };
+    (gain * diversity / reference_strength() * VALUE_SCALE).max(0.0)
+}
@@ -1,8 +1,21 @@
 pub mod behaviour;
+pub mod cover;
+pub mod exploration;
+pub mod ghost;
 pub mod holder;
 pub mod loot;
+pub mod objective;
 pub mod potential_field;
+pub mod retreat;
+pub mod shots;
+pub mod sound;
+pub mod sustain;
+pub mod targeting;
+pub mod team;
 pub mod util;
+pub mod vision;
+pub mod weapons;
+pub mod zone;
 
 use crate::debug_interface::DebugInterface;
 use crate::debugging::GREEN;
@@ -12,46 +25,85 @@ use crate::model::{ActionOrder, Constants, Game, Item, Loot, Unit, UnitOrder, Ve
 use crate::strategy::behaviour::behaviour::Behaviour;
 use crate::strategy::behaviour::fighting::Fighting;
 use crate::strategy::behaviour::ghosting::Ghosting;
+use crate::strategy::behaviour::kiting::Kiting;
 use crate::strategy::behaviour::move_or_loot::MoveOrLoot;
+use crate::strategy::behaviour::peeking::Peeking;
 use crate::strategy::behaviour::run_and_heal::RunAndHeal;
+use crate::strategy::behaviour::scouting::Scouting;
+use crate::strategy::behaviour::tree::{run, Blackboard, Node, UtilitySelector};
 use crate::strategy::holder::{get_constants, get_game};
+use crate::strategy::loot::allocation::allocate_loot;
+use crate::strategy::objective::create_objective;
+use crate::strategy::targeting::assign_targets;
+use crate::strategy::team::create_team_plan;
+use crate::strategy::vision::coordinate_vision;
+use crate::strategy::zone::create_zone_plan;
 use itertools::Itertools;
 use std::collections::HashMap;
 use std::ops::Index;
 
+static GHOSTING: Ghosting = Ghosting {};
+static FIGHTING: Fighting = Fighting {};
+static KITING: Kiting = Kiting {};
+static PEEKING: Peeking = Peeking {};
+static RUN_AND_HEAL: RunAndHeal = RunAndHeal {};
+static MOVE_OR_LOOT: MoveOrLoot = MoveOrLoot {};
+static SCOUTING: Scouting = Scouting {};
+static mut UNIT_TREE: Option<Box<dyn Node>> = None;
+
+// the tree only depends on the behaviours, it is built once on the first tick
+fn unit_tree() -> &'static dyn Node {
+    unsafe {
+        if UNIT_TREE.is_none() {
+            UNIT_TREE = Some(build_unit_tree());
+        }
+        UNIT_TREE.as_deref().unwrap()
+    }
+}
+
+fn build_unit_tree() -> Box<dyn Node> {
+    let behaviours: Vec<&'static dyn Behaviour> = vec![
+        &GHOSTING,
+        &PEEKING,
+        &KITING,
+        &FIGHTING,
+        &RUN_AND_HEAL,
+        &SCOUTING,
+        &MOVE_OR_LOOT,
+    ];
+    Box::new(UtilitySelector {
+        name: "Unit",
+        options: behaviours.into_iter().map(|b| (b, b.tree())).collect(),
+    })
+}
+
 pub fn get_order(debug_interface: &mut Option<&mut DebugInterface>) -> model::Order {
     let game = get_game();
     let constants = get_constants();
 
-    let behaviours: Vec<Box<dyn Behaviour>> = vec![
-        Box::new(Ghosting {}),
-        Box::new(Fighting {}),
-        Box::new(RunAndHeal {}),
-        Box::new(MoveOrLoot {}),
-    ];
+    let tree = unit_tree();
 
-    let orders: HashMap<i32, UnitOrder> = game
+    create_objective(debug_interface);
+    create_zone_plan(debug_interface);
+    assign_targets(debug_interface);
+    create_team_plan(debug_interface);
+    allocate_loot(debug_interface);
+
+    let mut orders: HashMap<i32, UnitOrder> = game
         .my_units()
         .into_iter()
         .sorted_by_key(|e| e.id)
         .map(|u| {
-            let mut order: UnitOrder = UnitOrder {
-                target_velocity: Default::default(),
-                target_direction: Default::default(),
-                action: None,
-            };
+            let mut blackboard = Blackboard::new(u);
             if let Some(debug) = debug_interface.as_mut() {
                 // debug.add_circle(u.position.clone(), 23.0, GREEN.clone())
             }
-            for behaviour in &behaviours {
-                if behaviour.should_use(u) {
-                    order = behaviour.order(u, debug_interface);
-                    break;
-                }
-            }
-            (u.id, order)
+            run(tree, &mut blackboard, debug_interface);
+            blackboard.draw_active_path(debug_interface);
+            (u.id, blackboard.order)
         })
         .collect();
+    coordinate_vision(&mut orders, debug_interface);
 
     model::Order {
         unit_orders: orders,
@@ -0,0 +1,143 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::TRANSPARENT_BLACK;
+use crate::model::{Player, Unit, Vec2};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_game, get_objective, set_objective,
+};
+
+// win probability required to take a fight for the most and the least aggressive play
+const MIN_WIN_PROBABILITY: f64 = 0.3;
+const MAX_WIN_PROBABILITY: f64 = 0.6;
+// behind the team above us by more than survival can give, only fighting closes the gap
+const CATCH_UP_AGGRESSION: f64 = 0.3;
+// enemy without a known teammate that close is considered the last one of its team
+const TEAMMATE_RADIUS: f64 = 30.0;
+
+// Marginal score of a kill, of damage and of outliving the other teams, and how
+// aggressive the team should play given the current standings.
+#[derive(Clone, Debug)]
+pub struct Objective {
+    pub kill_value: f64,
+    // per point of damage dealt
+    pub damage_value: f64,
+    // placement points still to gain by outliving every team alive
+    pub placement_value: f64,
+    // 0 plays only for survival, 1 only for kills and damage
+    pub aggression: f64,
+}
+
+impl Objective {
+    pub const fn const_default() -> Self {
+        Objective {
+            kill_value: 0.0,
+            damage_value: 0.0,
+            placement_value: 0.0,
+            aggression: 0.5,
+        }
+    }
+
+    pub fn required_win_probability(&self) -> f64 {
+        MAX_WIN_PROBABILITY - (MAX_WIN_PROBABILITY - MIN_WIN_PROBABILITY) * self.aggression
+    }
+
+    // score we get for killing the enemy from its current health
+    pub fn enemy_value(&self, enemy: &Unit) -> f64 {
+        let damage = (enemy.health + enemy.shield) * self.damage_value;
+        // eliminating the last unit of a team moves us a place up
+        let last_of_team = enemy.extra_lives == 0
+            && !get_all_enemy_units().iter().any(|e| {
+                e.id != enemy.id
+                    && e.player_id == enemy.player_id
+                    && e.position.distance(&enemy.position) < TEAMMATE_RADIUS
+            });
+        let placement = if last_of_team {
+            get_constants().score_per_place
+        } else {
+            0.0
+        };
+        self.kill_value + damage + placement
+    }
+
+    // enemy value against a kill of a fully healthy unit, 1 when score doesn't depend on it
+    pub fn relative_enemy_value(&self, enemy: &Unit) -> f64 {
+        let constants = get_constants();
+        let reference =
+            self.kill_value + (constants.unit_health + constants.max_shield) * self.damage_value;
+        if reference <= 0.0 {
+            return 1.0;
+        }
+        self.enemy_value(enemy) / reference
+    }
+}
+
+pub fn create_objective(debug_interface: &mut Option<&mut DebugInterface>) {
+    let constants = get_constants();
+    let game = get_game();
+
+    let alive_teams = alive_teams(&game.players);
+    let placement_value = constants.score_per_place * (alive_teams - 1).max(0) as f64;
+    let damage_value = constants.damage_score_multiplier;
+    let fight_value =
+        constants.kill_score + (constants.unit_health + constants.max_shield) * damage_value;
+
+    // the more lives the team has left, the less one death risks the placement
+    let lives = game
+        .my_units()
+        .iter()
+        .map(|u| 1 + u.extra_lives)
+        .sum::<i32>()
+        .max(1);
+    let placement_at_risk = placement_value / lives as f64;
+    let mut aggression = if fight_value + placement_at_risk > 0.0 {
+        fight_value / (fight_value + placement_at_risk)
+    } else {
+        0.5
+    };
+    if score_gap_above(&game.players, game.my_id) > placement_value {
+        aggression += CATCH_UP_AGGRESSION;
+    }
+    let objective = Objective {
+        kill_value: constants.kill_score,
+        damage_value,
+        placement_value,
+        aggression: aggression.clamp(0.0, 1.0),
+    };
+
+    if let Some(debug) = debug_interface.as_mut() {
+        let position = game
+            .my_units()
+            .first()
+            .map_or(Vec2::default(), |u| u.position);
+        debug.add_placed_text(
+            position + Vec2 { x: 0.0, y: -3.0 },
+            format!("aggression {:.2}", objective.aggression),
+            Vec2 { x: 0.5, y: 0.5 },
+            0.8,
+            TRANSPARENT_BLACK,
+        );
+    }
+    set_objective(objective);
+}
+
+pub fn required_win_probability() -> f64 {
+    get_objective().required_win_probability()
+}
+
+// alive teams share the lowest place, dead ones keep the place they died at
+fn alive_teams(players: &[Player]) -> i32 {
+    players.iter().map(|p| p.place).min().unwrap_or(1)
+}
+
+// how far the closest team above us is, nothing when we lead
+fn score_gap_above(players: &[Player], my_id: i32) -> f64 {
+    let my_score = match players.iter().find(|p| p.id == my_id) {
+        Some(me) => me.score,
+        None => return 0.0,
+    };
+    players
+        .iter()
+        .filter(|p| p.score > my_score)
+        .map(|p| p.score - my_score)
+        .min_by(|g1, g2| g1.partial_cmp(g2).unwrap())
+        .unwrap_or(0.0)
+}
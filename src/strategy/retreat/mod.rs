@@ -0,0 +1,174 @@
+use crate::model::{Obstacle, Unit, Vec2};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_game, get_obstacles, get_team_plan,
+};
+use crate::strategy::sustain::has_drinking_window;
+use crate::strategy::util::{intersects_with_obstacles_by, intersects_with_obstacles_vec};
+use itertools::Itertools;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// the search covers that many steps in every direction from the unit
+const SEARCH_RADIUS: i32 = 15;
+const STEP: f64 = 1.0;
+const ZONE_MARGIN: f64 = 3.0;
+// walking in the open costs that much more per enemy watching the step
+const EXPOSED_STEP_WEIGHT: f64 = 1.0;
+// a destination somebody still sees or shoots is worth that much walking to avoid
+const EXPOSED_PENALTY: f64 = 20.0;
+// hiding spots where the potion gets interrupted are worse when we want to drink
+const NO_DRINKING_WINDOW_PENALTY: f64 = 10.0;
+const RETREAT_POINT_WEIGHT: f64 = 0.3;
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Clone, Debug)]
+pub struct RetreatPlan {
+    pub destination: Vec2,
+    // from the unit to the destination
+    pub path: Vec<Vec2>,
+    // enemies still seeing or able to shoot the destination
+    pub exposure: usize,
+}
+
+impl RetreatPlan {
+    // first waypoint further than a step, the local movement takes care of the rest
+    pub fn next_waypoint(&self, from: &Vec2) -> Vec2 {
+        self.path
+            .iter()
+            .find(|p| p.distance(from) > STEP * 1.5)
+            .copied()
+            .unwrap_or(self.destination)
+    }
+}
+
+// Closest reachable point hidden from the sight and the fire of every known enemy, reached by
+// a path avoiding their lines of fire. None when there is nobody to hide from or no way to go.
+pub fn plan_retreat(unit: &Unit, wants_to_drink: bool) -> Option<RetreatPlan> {
+    let constants = get_constants();
+    let search_distance = SEARCH_RADIUS as f64 * STEP;
+    let enemies = get_all_enemy_units()
+        .iter()
+        .filter(|e| e.remaining_spawn_time.is_none())
+        .filter(|e| {
+            e.position.distance(&unit.position)
+                < constants.view_distance.max(e.firing_distance()) + search_distance
+        })
+        .collect_vec();
+    if enemies.is_empty() {
+        return None;
+    }
+    let obstacles = get_obstacles(unit.id);
+    let zone = &get_game().zone;
+
+    let size = (2 * SEARCH_RADIUS + 1) as usize;
+    let point = |i: usize| Vec2 {
+        x: unit.position.x + ((i % size) as i32 - SEARCH_RADIUS) as f64 * STEP,
+        y: unit.position.y + ((i / size) as i32 - SEARCH_RADIUS) as f64 * STEP,
+    };
+    let passable = |p: &Vec2| {
+        p.distance(&unit.position) <= search_distance
+            && zone.current_center.distance(p) + ZONE_MARGIN < zone.current_radius
+            && obstacles
+                .iter()
+                .all(|o| o.position.distance(p) >= o.radius + constants.unit_radius)
+    };
+
+    let start = SEARCH_RADIUS as usize * size + SEARCH_RADIUS as usize;
+    let mut exposure: Vec<Option<usize>> = vec![None; size * size];
+    let mut cost = vec![f64::INFINITY; size * size];
+    let mut parent = vec![start; size * size];
+    let mut visited = vec![false; size * size];
+    let mut heap = BinaryHeap::new();
+    exposure[start] = Some(exposed_to(&unit.position, &enemies, &obstacles));
+    cost[start] = 0.0;
+    heap.push((Reverse(0), start));
+    while let Some((_, i)) = heap.pop() {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+        let (x, y) = ((i % size) as i32, (i / size) as i32);
+        for (dx, dy) in NEIGHBOURS {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= size as i32 || ny >= size as i32 {
+                continue;
+            }
+            let j = ny as usize * size + nx as usize;
+            let p = point(j);
+            if visited[j] || !passable(&p) {
+                continue;
+            }
+            let exposed = *exposure[j].get_or_insert_with(|| exposed_to(&p, &enemies, &obstacles));
+            let step = ((dx * dx + dy * dy) as f64).sqrt()
+                * STEP
+                * (1.0 + exposed as f64 * EXPOSED_STEP_WEIGHT);
+            if cost[i] + step < cost[j] {
+                cost[j] = cost[i] + step;
+                parent[j] = i;
+                // the heap needs a total order, millimetres are precise enough
+                heap.push((Reverse((cost[j] * 1000.0) as i64), j));
+            }
+        }
+    }
+
+    // nowhere to go, e.g. outside the zone with every step around out of it as well
+    if visited.iter().filter(|v| **v).count() <= 1 {
+        return None;
+    }
+    // staying is no option when the unit stands where it can't stay
+    let start_allowed = passable(&unit.position);
+    let retreat_point = get_team_plan().retreat_point;
+    let (destination, exposed, _) = (0..size * size)
+        .filter(|i| visited[*i] && (*i != start || start_allowed))
+        .map(|i| {
+            let p = point(i);
+            let exposed = exposure[i].unwrap_or(0);
+            let drinking = if wants_to_drink && exposed == 0 && !has_drinking_window(&p) {
+                NO_DRINKING_WINDOW_PENALTY
+            } else {
+                0.0
+            };
+            let retreat = retreat_point.map_or(0.0, |r| r.distance(&p) * RETREAT_POINT_WEIGHT);
+            let score = cost[i] + exposed as f64 * EXPOSED_PENALTY + drinking + retreat;
+            (i, exposed, score)
+        })
+        .min_by(|(_, _, s1), (_, _, s2)| s1.partial_cmp(s2).unwrap())?;
+
+    let mut path = vec![point(destination)];
+    let mut current = destination;
+    while current != start {
+        current = parent[current];
+        path.push(point(current));
+    }
+    path.reverse();
+    Some(RetreatPlan {
+        destination: point(destination),
+        path,
+        exposure: exposed,
+    })
+}
+
+// enemies seeing the point or able to shoot it
+fn exposed_to(p: &Vec2, enemies: &[&Unit], obstacles: &[Obstacle]) -> usize {
+    let constants = get_constants();
+    enemies
+        .iter()
+        .filter(|e| {
+            let distance = e.position.distance(p);
+            let sees = distance <= constants.view_distance
+                && !intersects_with_obstacles_by(&e.position, p, obstacles, |o| !o.can_see_through);
+            let shoots = distance <= e.firing_distance() + constants.unit_radius
+                && !intersects_with_obstacles_vec(&e.position, p, obstacles);
+            sees || shoots
+        })
+        .count()
+}
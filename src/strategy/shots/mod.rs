@@ -0,0 +1,192 @@
+use crate::model::{Obstacle, Projectile, Unit, Vec2};
+use crate::strategy::holder::{get_all_enemy_units, get_constants, get_game};
+use crate::strategy::util::rotate;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+// rays spread evenly over the weapon spread cone
+const RAYS: i32 = 15;
+const MIN_HIT_PROBABILITY: f64 = 0.1;
+const MAX_FRIENDLY_HIT_PROBABILITY: f64 = 0.05;
+// hitting another enemy instead of the target still scores damage
+const OTHER_ENEMY_VALUE: f64 = 0.5;
+
+// Chance of a shot hitting every unit it can reach. The projectile stops at the first unit
+// or shoot blocking obstacle on its way.
+#[derive(Clone, Debug)]
+pub struct ShotTrace {
+    pub hits: Vec<(i32, f64)>,
+}
+
+impl ShotTrace {
+    pub fn probability(&self, unit_id: i32) -> f64 {
+        self.hits
+            .iter()
+            .filter(|(id, _)| *id == unit_id)
+            .map(|(_, p)| p)
+            .sum()
+    }
+}
+
+pub fn trace_shot(shooter: &Unit, aim_point: &Vec2, obstacles: &[Obstacle]) -> ShotTrace {
+    let weapon = match shooter.weapon {
+        None => return ShotTrace { hits: vec![] },
+        Some(w) => &get_constants().weapons[w as usize],
+    };
+    let units = hittable_units(shooter.id, shooter.player_id);
+    let angle = (*aim_point - shooter.position).angle();
+    let half_spread = weapon.spread.to_radians() / 2.0;
+    let mut hits: HashMap<i32, f64> = HashMap::new();
+    for i in 0..RAYS {
+        let offset = -half_spread + 2.0 * half_spread * i as f64 / (RAYS - 1) as f64;
+        let ray = rotate(Vec2::default(), angle + offset, 1.0);
+        let hit = first_unit_hit(
+            &shooter.position,
+            &ray,
+            weapon.firing_distance(),
+            &units,
+            obstacles,
+        );
+        if let Some(id) = hit {
+            *hits.entry(id).or_insert(0.0) += 1.0 / RAYS as f64;
+        }
+    }
+    ShotTrace {
+        hits: hits.into_iter().sorted_by_key(|(id, _)| *id).collect_vec(),
+    }
+}
+
+// the shot is likely to damage an enemy, preferably the target, without hurting our units
+pub fn is_worth_shooting(
+    shooter: &Unit,
+    target: &Unit,
+    aim_point: &Vec2,
+    obstacles: &[Obstacle],
+) -> bool {
+    let my_id = get_game().my_id;
+    let trace = trace_shot(shooter, aim_point, obstacles);
+    let friendly = trace
+        .hits
+        .iter()
+        .filter(|(id, _)| get_game().my_units().iter().any(|u| u.id == *id))
+        .map(|(_, p)| p)
+        .sum::<f64>();
+    let other_enemies = trace
+        .hits
+        .iter()
+        .filter(|(id, _)| *id != target.id)
+        .filter(|(id, _)| {
+            get_all_enemy_units()
+                .iter()
+                .any(|e| e.id == *id && e.player_id != my_id)
+        })
+        .map(|(_, p)| p)
+        .sum::<f64>();
+    let value = trace.probability(target.id) + other_enemies * OTHER_ENEMY_VALUE;
+    value >= MIN_HIT_PROBABILITY && friendly <= MAX_FRIENDLY_HIT_PROBABILITY
+}
+
+// the straight line from the point to the target isn't blocked by obstacles or other units
+pub fn is_line_of_fire_clear(
+    shooter: &Unit,
+    from: &Vec2,
+    target: &Unit,
+    obstacles: &[Obstacle],
+) -> bool {
+    let distance = from.distance(&target.position);
+    if distance < f64::EPSILON {
+        return true;
+    }
+    let units = hittable_units(shooter.id, shooter.player_id);
+    let ray = (target.position - *from) / distance;
+    first_unit_hit(
+        from,
+        &ray,
+        distance + get_constants().unit_radius,
+        &units,
+        obstacles,
+    ) == Some(target.id)
+}
+
+// Seconds the projectile keeps flying before an obstacle or an enemy unit in the way stops it.
+// Our units are left out, they are the ones dodging it.
+pub fn projectile_life_time(projectile: &Projectile) -> f64 {
+    let constants = get_constants();
+    let my_id = get_game().my_id;
+    if projectile.shooter_player_id == my_id && !constants.friendly_fire {
+        return 0.0;
+    }
+    let speed = projectile.velocity.len();
+    if speed < f64::EPSILON {
+        return projectile.life_time;
+    }
+    let ray = projectile.velocity / speed;
+    let range = speed * projectile.life_time;
+    let obstacle = first_obstacle_hit(&projectile.position, &ray, range, &constants.obstacles);
+    let unit = hittable_units(projectile.shooter_id, projectile.shooter_player_id)
+        .into_iter()
+        .filter(|u| u.player_id != my_id)
+        .filter_map(|u| {
+            ray_circle(
+                &projectile.position,
+                &ray,
+                &u.position,
+                constants.unit_radius,
+            )
+        })
+        .fold(obstacle, f64::min);
+    unit / speed
+}
+
+// units a projectile of the player can hit: ghosts are not there, teammates only with friendly fire
+fn hittable_units(shooter_id: i32, shooter_player_id: i32) -> Vec<&'static Unit> {
+    let friendly_fire = get_constants().friendly_fire;
+    get_game()
+        .my_units()
+        .into_iter()
+        .chain(get_all_enemy_units().iter())
+        .filter(|u| u.id != shooter_id && u.remaining_spawn_time.is_none())
+        .filter(|u| friendly_fire || u.player_id != shooter_player_id)
+        .collect_vec()
+}
+
+fn first_unit_hit(
+    from: &Vec2,
+    ray: &Vec2,
+    range: f64,
+    units: &[&Unit],
+    obstacles: &[Obstacle],
+) -> Option<i32> {
+    let unit_radius = get_constants().unit_radius;
+    let obstacle = first_obstacle_hit(from, ray, range, obstacles);
+    units
+        .iter()
+        .filter_map(|u| ray_circle(from, ray, &u.position, unit_radius).map(|t| (u.id, t)))
+        .filter(|(_, t)| *t < obstacle)
+        .min_by(|(_, t1), (_, t2)| t1.partial_cmp(t2).unwrap())
+        .map(|(id, _)| id)
+}
+
+// distance to the first shoot blocking obstacle, the range when there is none
+fn first_obstacle_hit(from: &Vec2, ray: &Vec2, range: f64, obstacles: &[Obstacle]) -> f64 {
+    obstacles
+        .iter()
+        .filter(|o| !o.can_shoot_through)
+        .filter_map(|o| ray_circle(from, ray, &o.position, o.radius))
+        .fold(range, f64::min)
+}
+
+// distance along the ray to the circle, none when the ray misses it
+fn ray_circle(from: &Vec2, ray: &Vec2, center: &Vec2, radius: f64) -> Option<f64> {
+    let to = *center - *from;
+    let along = to.x * ray.x + to.y * ray.y;
+    let across_squared = to.x * to.x + to.y * to.y - along * along;
+    if across_squared >= radius * radius {
+        return None;
+    }
+    let half_chord = (radius * radius - across_squared).sqrt();
+    if along + half_chord < 0.0 {
+        return None;
+    }
+    Some((along - half_chord).max(0.0))
+}
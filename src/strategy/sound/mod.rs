@@ -0,0 +1,120 @@
+use crate::model::{Game, Sound, Unit, Vec2};
+use crate::strategy::holder::tracker::Observation;
+use crate::strategy::holder::{get_constants, get_unexplained_sounds};
+use itertools::Itertools;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SoundKind {
+    Steps,
+    Shot { weapon: i32 },
+    // where a projectile hit something, says nothing about the shooter position
+    Hit { weapon: i32 },
+}
+
+#[derive(Clone, Debug)]
+pub struct SoundInference {
+    pub kind: SoundKind,
+    pub tick: i32,
+    // heard position, the source is around it
+    pub position: Vec2,
+    // radius around the position the source is in
+    pub uncertainty: f64,
+}
+
+pub fn sound_kind(type_index: i32) -> Option<SoundKind> {
+    let constants = get_constants();
+    if constants.steps_sound_type_index == Some(type_index) {
+        return Some(SoundKind::Steps);
+    }
+    for (i, weapon) in constants.weapons.iter().enumerate() {
+        if weapon.shot_sound_type_index == Some(type_index) {
+            return Some(SoundKind::Shot { weapon: i as i32 });
+        }
+        if weapon.projectile_hit_sound_type_index == Some(type_index) {
+            return Some(SoundKind::Hit { weapon: i as i32 });
+        }
+    }
+    None
+}
+
+// The heard position is shifted from the source by up to offset * distance to the source,
+// so the further the sound was from the listener, the less we know where it came from.
+pub fn interpret(sound: &Sound, listener: &Unit, tick: i32) -> Option<SoundInference> {
+    let properties = get_constants().sounds.get(sound.type_index as usize)?;
+    let heard_distance = listener.position.distance(&sound.position);
+    let max_offset = properties.offset * heard_distance / (1.0 - properties.offset).max(0.1);
+    Some(SoundInference {
+        kind: sound_kind(sound.type_index)?,
+        tick,
+        position: sound.position,
+        uncertainty: max_offset / 2.0,
+    })
+}
+
+pub fn interpret_all(game: &Game) -> Vec<SoundInference> {
+    game.sounds
+        .iter()
+        .filter_map(|s| {
+            let listener = game.my_units().into_iter().find(|u| u.id == s.unit_id)?;
+            interpret(s, listener, game.current_tick)
+        })
+        .collect_vec()
+}
+
+// steps and shots give us the source position, hits only tell that somebody is shooting
+pub fn sound_observations(game: &Game, sounds: &[SoundInference]) -> Vec<Observation> {
+    sounds
+        .iter()
+        .filter(|s| !could_be_ours(game, s))
+        .filter_map(|s| {
+            let weapon = match s.kind {
+                SoundKind::Steps => None,
+                SoundKind::Shot { weapon } => Some(weapon),
+                SoundKind::Hit { .. } => return None,
+            };
+            Some(Observation {
+                unit_id: None,
+                player_id: None,
+                position: s.position,
+                uncertainty: s.uncertainty,
+                weapon,
+            })
+        })
+        .collect_vec()
+}
+
+// none of the enemies we see could have made it
+pub fn is_unexplained(game: &Game, sound: &SoundInference) -> bool {
+    !could_be_ours(game, sound)
+        && game.enemy_units().iter().all(|e| {
+            e.position.distance(&sound.position)
+                > sound.uncertainty + get_constants().unit_radius * 2.0
+        })
+}
+
+fn could_be_ours(game: &Game, sound: &SoundInference) -> bool {
+    match sound.kind {
+        SoundKind::Hit { .. } => false,
+        _ => game.my_units().iter().any(|u| {
+            u.position.distance(&sound.position)
+                <= sound.uncertainty + get_constants().unit_radius * 2.0
+        }),
+    }
+}
+
+// latest unexplained sound the unit could hear and doesn't look at already
+pub fn sound_to_look_at(unit: &Unit) -> Option<Vec2> {
+    let constants = get_constants();
+    get_unexplained_sounds()
+        .iter()
+        .filter(|s| s.position.distance(&unit.position) < constants.view_distance * 1.5)
+        .filter(|s| {
+            // sounds beyond the view distance are looked at when we face them
+            let distance = s.position.distance(&unit.position).max(f64::EPSILON);
+            let towards =
+                (s.position - unit.position) * (constants.view_distance / distance).min(1.0);
+            !unit.is_inside_vision(&(unit.position + towards))
+        })
+        .max_by_key(|s| s.tick)
+        .map(|s| s.position - unit.position)
+}
@@ -0,0 +1,147 @@
+use crate::model::{ActionType, Unit, Vec2};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_cover_map, get_game, get_objective, get_projectiles,
+};
+
+// extra time we want left after the potion is drunk
+const SAFETY_MARGIN_SECONDS: f64 = 0.5;
+// a fully covered spot makes the enemy walk around the obstacle first
+const COVER_DELAY_SECONDS: f64 = 1.0;
+// below this share of the full health and shield we leave the fight to heal,
+// cautious play leaves earlier and aggressive play later
+const DISENGAGE_RATIO: f64 = 0.4;
+const DISENGAGE_AGGRESSION_SHIFT: f64 = 0.2;
+// below this share of the health we rather wait for regeneration than take a fight
+const REGENERATE_RATIO: f64 = 0.5;
+// threats further than that in time don't matter
+const MAX_THREAT_SECONDS: f64 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sustain {
+    // nothing to heal or nothing to heal with
+    Nothing,
+    // the potion is being drunk, don't interrupt it by aiming
+    Drinking,
+    // potion makes sense and there is enough time to drink it
+    DrinkNow,
+    // potion makes sense but somebody would interrupt it
+    WaitForWindow,
+    // wounded without potions, stay away until the health regenerates
+    Regenerate,
+    // too weak to continue the fight
+    Disengage,
+}
+
+// seconds until an enemy or a flying projectile can hit the point
+pub fn seconds_until_threat(p: &Vec2) -> f64 {
+    let constants = get_constants();
+    let cover_map = get_cover_map();
+    let from_enemies = get_all_enemy_units()
+        .iter()
+        .filter(|e| e.weapon.is_some() && e.ammo_for_current_weapon() > 0)
+        .map(|e| {
+            let weapon = &constants.weapons[e.weapon.unwrap() as usize];
+            let distance = e.position.distance(p);
+            let approach =
+                (distance - weapon.firing_distance()).max(0.0) / constants.max_unit_forward_speed;
+            let spawn = e.remaining_spawn_time.unwrap_or(0.0);
+            let aim = weapon.aim_time * (1.0 - e.aim);
+            let flight = distance.min(weapon.firing_distance()) / weapon.projectile_speed;
+            let cover = if cover_map.cover(p, &e.position) >= 1.0 {
+                COVER_DELAY_SECONDS
+            } else {
+                0.0
+            };
+            spawn.max(approach) + aim + flight + cover
+        })
+        .fold(MAX_THREAT_SECONDS, f64::min);
+    let from_projectiles = get_projectiles()
+        .iter()
+        .filter(|pr| pr.shooter_player_id != get_game().my_id)
+        .filter_map(|pr| {
+            let to = *p - pr.position;
+            let speed = pr.velocity.x * pr.velocity.x + pr.velocity.y * pr.velocity.y;
+            let t = (to.x * pr.velocity.x + to.y * pr.velocity.y) / speed.max(f64::EPSILON);
+            let closest = pr.position + pr.velocity * t;
+            if t >= 0.0 && t <= pr.life_time && closest.distance(p) < constants.unit_radius {
+                Some(t)
+            } else {
+                None
+            }
+        })
+        .fold(MAX_THREAT_SECONDS, f64::min);
+    from_enemies.min(from_projectiles)
+}
+
+// seconds until the health regeneration starts, it restarts with every hit taken
+pub fn seconds_until_regeneration(unit: &Unit) -> f64 {
+    let ticks = unit.health_regeneration_start_tick - get_game().current_tick;
+    ticks.max(0) as f64 / get_constants().ticks_per_second
+}
+
+// seconds until the unit is fully healed by regeneration alone, infinite when it doesn't regenerate
+pub fn seconds_to_regenerate(unit: &Unit) -> f64 {
+    let constants = get_constants();
+    let missing = constants.unit_health - unit.health;
+    if missing <= 0.0 {
+        return 0.0;
+    }
+    if constants.health_regeneration_per_second <= 0.0 {
+        return f64::INFINITY;
+    }
+    seconds_until_regeneration(unit) + missing / constants.health_regeneration_per_second
+}
+
+pub fn has_drinking_window(p: &Vec2) -> bool {
+    seconds_until_threat(p) >= get_constants().shield_potion_use_time + SAFETY_MARGIN_SECONDS
+}
+
+pub fn sustain(unit: &Unit) -> Sustain {
+    let constants = get_constants();
+    let drinking = matches!(
+        unit.action.as_ref().map(|a| &a.action_type),
+        Some(ActionType::UseShieldPotion)
+    );
+    if drinking {
+        return Sustain::Drinking;
+    }
+    let durability = (unit.health + unit.shield) / (constants.unit_health + constants.max_shield);
+    let disengage_ratio =
+        DISENGAGE_RATIO + (0.5 - get_objective().aggression) * DISENGAGE_AGGRESSION_SHIFT * 2.0;
+    let wounded = unit.health < constants.unit_health * REGENERATE_RATIO;
+    // a potion is wasted unless the whole shield it gives fits
+    let potion_fits = unit.shield + constants.shield_per_potion <= constants.max_shield;
+    let potion_needed = unit.shield_potions > 0
+        && (potion_fits || unit.shield < constants.max_shield && durability < disengage_ratio);
+
+    let threat = seconds_until_threat(&unit.position);
+    let heal_seconds = seconds_to_regenerate(unit);
+
+    if potion_needed && unit.action.is_none() {
+        if threat >= constants.shield_potion_use_time + SAFETY_MARGIN_SECONDS {
+            return Sustain::DrinkNow;
+        }
+        // the health is back before anybody arrives, the potion waits for a safer moment
+        if wounded && heal_seconds <= threat {
+            return Sustain::Regenerate;
+        }
+        return if durability < disengage_ratio {
+            Sustain::Disengage
+        } else {
+            Sustain::WaitForWindow
+        };
+    }
+    if durability < disengage_ratio {
+        return Sustain::Disengage;
+    }
+    // staying away only helps when the health comes back at all
+    if wounded && heal_seconds.is_finite() {
+        return Sustain::Regenerate;
+    }
+    Sustain::Nothing
+}
+
+// the fight has to wait until the unit heals
+pub fn should_disengage(unit: &Unit) -> bool {
+    matches!(sustain(unit), Sustain::Drinking | Sustain::Disengage)
+}
@@ -0,0 +1,162 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::TRANSPARENT_ORANGE;
+use crate::model::{Unit, WeaponProperties};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_enemy_clusters, get_fight_simulations, get_game,
+    get_objective, get_obstacles, set_targets,
+};
+use crate::strategy::util::intersects_with_obstacles_vec;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+// share of the damage that still lands when the obstacles are in the way, we can reposition
+const BLOCKED_LINE_OF_FIRE_MODIFIER: f64 = 0.3;
+// how many times assignments are revisited after the greedy pass
+const IMPROVEMENT_PASSES: i32 = 2;
+// units of one cluster rather finish the enemy cluster they fight than spread over several
+const FOCUS_CLUSTER_BONUS: f64 = 1.5;
+
+// enemies from the simulations this unit is allowed to take
+pub fn fightable_enemies(unit: &Unit) -> Vec<&'static Unit> {
+    get_fight_simulations()
+        .iter()
+        .filter(|s| {
+            s.allies.contains(&unit.id) && (s.is_favourable() || get_game().current_tick > 6000)
+        })
+        .flat_map(|s| s.enemy_units())
+        .unique_by(|e| e.id)
+        .collect_vec()
+}
+
+// enemy cluster of the most winnable fight the unit's cluster takes
+fn focus_cluster(unit: &Unit) -> Option<i32> {
+    get_fight_simulations()
+        .iter()
+        .filter(|s| s.allies.contains(&unit.id) && s.is_favourable())
+        .max_by(|s1, s2| {
+            s1.result
+                .win_probability
+                .partial_cmp(&s2.result.win_probability)
+                .unwrap()
+        })
+        .map(|s| s.cluster_id)
+}
+
+pub fn assign_targets(debug_interface: &mut Option<&mut DebugInterface>) {
+    let allies = get_game()
+        .my_units()
+        .into_iter()
+        .filter(|u| u.remaining_spawn_time.is_none())
+        .filter(|u| u.ammo_for_current_weapon() > 0)
+        .sorted_by_key(|u| u.id)
+        .collect_vec();
+
+    // damage per second every ally can put on every enemy it may fight
+    let damage = allies
+        .iter()
+        .map(|ally| {
+            fightable_enemies(ally)
+                .into_iter()
+                .map(|e| (e.id, damage_per_second(ally, e)))
+                .filter(|(_, dps)| *dps > 0.0)
+                .collect::<HashMap<i32, f64>>()
+        })
+        .collect_vec();
+    let focus_clusters = allies.iter().map(|ally| focus_cluster(ally)).collect_vec();
+
+    let mut assignment: Vec<Option<i32>> = vec![None; allies.len()];
+    for _ in 0..IMPROVEMENT_PASSES {
+        for i in 0..allies.len() {
+            assignment[i] = None;
+            let mut focused_damage: HashMap<i32, f64> = HashMap::new();
+            for (j, target) in assignment.iter().enumerate() {
+                if let Some(target) = target {
+                    *focused_damage.entry(*target).or_insert(0.0) += damage[j][target];
+                }
+            }
+            assignment[i] = damage[i]
+                .iter()
+                .map(|(enemy_id, dps)| {
+                    let enemy = get_all_enemy_units()
+                        .iter()
+                        .find(|e| e.id == *enemy_id)
+                        .unwrap();
+                    let team_dps = focused_damage.get(enemy_id).unwrap_or(&0.0) + dps;
+                    let time_to_kill = (enemy.health + enemy.shield) / team_dps;
+                    let value = get_objective().relative_enemy_value(enemy);
+                    let focus = if get_enemy_clusters()
+                        .iter()
+                        .any(|c| Some(c.id) == focus_clusters[i] && c.contains(*enemy_id))
+                    {
+                        FOCUS_CLUSTER_BONUS
+                    } else {
+                        1.0
+                    };
+                    let score = (1.0 + threat(enemy)) * value * focus / time_to_kill;
+                    (*enemy_id, score)
+                })
+                .max_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).unwrap())
+                .map(|(id, _)| id);
+        }
+    }
+
+    if let Some(debug) = debug_interface.as_mut() {
+        for (ally, target) in allies.iter().zip(assignment.iter()) {
+            if let Some(target) = get_all_enemy_units().iter().find(|e| Some(e.id) == *target) {
+                debug.add_segment(ally.position, target.position, 0.1, TRANSPARENT_ORANGE);
+            }
+        }
+    }
+
+    set_targets(
+        allies
+            .iter()
+            .zip(assignment)
+            .filter_map(|(ally, target)| target.map(|t| (ally.id, t)))
+            .collect_vec(),
+    );
+}
+
+// how much damage per second the enemy can deal right now
+fn threat(enemy: &Unit) -> f64 {
+    match enemy.weapon {
+        None => 0.0,
+        Some(_) if enemy.ammo_for_current_weapon() == 0 => 0.0,
+        Some(w) => {
+            let weapon = &get_constants().weapons[w as usize];
+            weapon.projectile_damage * weapon.rounds_per_second * (0.5 + 0.5 * enemy.aim)
+        }
+    }
+}
+
+fn damage_per_second(ally: &Unit, enemy: &Unit) -> f64 {
+    let weapon = match ally.weapon {
+        None => return 0.0,
+        Some(w) => &get_constants().weapons[w as usize],
+    };
+    let distance = ally.position.distance(&enemy.position);
+    let reachability = if distance <= weapon.firing_distance() {
+        1.0
+    } else {
+        weapon.firing_distance() / distance
+    };
+    let line_of_fire = if intersects_with_obstacles_vec(
+        &ally.position,
+        &enemy.position,
+        &get_obstacles(ally.id),
+    ) {
+        BLOCKED_LINE_OF_FIRE_MODIFIER
+    } else {
+        1.0
+    };
+    weapon.projectile_damage
+        * weapon.rounds_per_second
+        * hit_probability(distance, weapon)
+        * reachability
+        * line_of_fire
+}
+
+pub fn hit_probability(distance: f64, weapon: &WeaponProperties) -> f64 {
+    let spread_width = 2.0 * distance * (weapon.spread.to_radians() / 2.0).tan();
+    (2.0 * get_constants().unit_radius / spread_width).min(1.0)
+}
@@ -0,0 +1,186 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::{TRANSPARENT_BLUE, TRANSPARENT_ORANGE};
+use crate::model::{Unit, Vec2};
+use crate::strategy::holder::{
+    get_all_enemy_units, get_constants, get_enemy_clusters, get_fight_simulations, get_game,
+    get_target, set_team_plan,
+};
+use crate::strategy::util::{centroid, rotate};
+use itertools::Itertools;
+
+// below this we pull everybody back instead of trading one by one
+const RETREAT_WIN_PROBABILITY: f64 = 0.3;
+const RETREAT_DISTANCE: f64 = 15.0;
+const FLANK_ANGLE: f64 = 0.6;
+// share of the zone radius the regroup point is kept inside
+const SAFE_ZONE_RATIO: f64 = 0.8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Role {
+    Looter,
+    Cover,
+}
+
+#[derive(Clone, Debug)]
+pub struct TeamPlan {
+    pub regroup_point: Vec2,
+    // point the whole team falls back to, None if we are not retreating
+    pub retreat_point: Option<Vec2>,
+    // angle around the target (relative to the team side) every unit attacks from
+    pub flanks: Vec<(i32, f64)>,
+    pub roles: Vec<(i32, Role)>,
+}
+
+impl TeamPlan {
+    pub const fn const_default() -> Self {
+        TeamPlan {
+            regroup_point: Vec2 { x: 0.0, y: 0.0 },
+            retreat_point: None,
+            flanks: vec![],
+            roles: vec![],
+        }
+    }
+
+    pub fn flank(&self, unit_id: i32) -> f64 {
+        self.flanks
+            .iter()
+            .find(|(id, _)| *id == unit_id)
+            .map(|(_, angle)| *angle)
+            .unwrap_or(0.0)
+    }
+
+    pub fn role(&self, unit_id: i32) -> Role {
+        self.roles
+            .iter()
+            .find(|(id, _)| *id == unit_id)
+            .map(|(_, role)| role.clone())
+            .unwrap_or(Role::Looter)
+    }
+
+    // point near the target this unit should fire from according to its flank
+    pub fn flank_point(&self, unit_id: i32, target: &Unit, distance: f64) -> Vec2 {
+        let team_side = (self.regroup_point - target.position).angle();
+        rotate(target.position, team_side + self.flank(unit_id), distance)
+    }
+}
+
+pub fn create_team_plan(debug_interface: &mut Option<&mut DebugInterface>) {
+    let units = get_game()
+        .my_units()
+        .into_iter()
+        .filter(|u| u.remaining_spawn_time.is_none())
+        .sorted_by_key(|u| u.id)
+        .collect_vec();
+    if units.is_empty() {
+        set_team_plan(TeamPlan::const_default());
+        return;
+    }
+
+    let regroup_point = inside_safe_zone(centroid(units.iter().map(|u| u.position)));
+    let retreat_point = retreat_point(&units, &regroup_point);
+    let plan = TeamPlan {
+        regroup_point,
+        retreat_point,
+        flanks: flanks(&units),
+        roles: roles(&units, &regroup_point),
+    };
+
+    if let Some(debug) = debug_interface.as_mut() {
+        debug.add_circle(plan.regroup_point, 1.0, TRANSPARENT_BLUE);
+        if let Some(retreat) = plan.retreat_point {
+            debug.add_segment(plan.regroup_point, retreat, 0.3, TRANSPARENT_ORANGE);
+        }
+    }
+    set_team_plan(plan);
+}
+
+fn retreat_point(units: &[&Unit], regroup_point: &Vec2) -> Option<Vec2> {
+    // we back off from the whole cluster, not just the units already in range
+    let threatening_clusters = get_fight_simulations()
+        .iter()
+        .filter(|s| s.result.win_probability < RETREAT_WIN_PROBABILITY)
+        .filter(|s| {
+            s.enemy_units().iter().any(|e| {
+                units
+                    .iter()
+                    .any(|u| u.position.distance(&e.position) <= e.firing_distance())
+            })
+        })
+        .map(|s| s.cluster_id)
+        .unique()
+        .collect_vec();
+    if threatening_clusters.is_empty() {
+        return None;
+    }
+    let enemies_center = centroid(
+        get_enemy_clusters()
+            .iter()
+            .filter(|c| threatening_clusters.contains(&c.id))
+            .map(|c| c.center),
+    );
+    let away = (*regroup_point - enemies_center).angle();
+    Some(inside_safe_zone(rotate(
+        *regroup_point,
+        away,
+        RETREAT_DISTANCE,
+    )))
+}
+
+// units sharing a target spread around it instead of lining up
+fn flanks(units: &[&Unit]) -> Vec<(i32, f64)> {
+    units
+        .iter()
+        .filter_map(|u| get_target(u.id).map(|t| (t.id, u.id)))
+        .into_group_map()
+        .into_values()
+        .flat_map(|attackers| {
+            let middle = (attackers.len() - 1) as f64 / 2.0;
+            attackers
+                .into_iter()
+                .sorted()
+                .enumerate()
+                .map(move |(i, id)| (id, (i as f64 - middle) * FLANK_ANGLE))
+        })
+        .collect_vec()
+}
+
+// with enemies around the best equipped unit covers while the others loot
+fn roles(units: &[&Unit], regroup_point: &Vec2) -> Vec<(i32, Role)> {
+    let enemies_nearby = get_all_enemy_units()
+        .iter()
+        .filter(|e| e.remaining_spawn_time.is_none())
+        .any(|e| e.position.distance(regroup_point) < get_constants().view_distance);
+    let cover = units
+        .iter()
+        .filter(|u| u.ammo_for_current_weapon() > 0)
+        .max_by(|a, b| {
+            (a.health + a.shield)
+                .partial_cmp(&(b.health + b.shield))
+                .unwrap()
+        })
+        .map(|u| u.id);
+    units
+        .iter()
+        .map(|u| {
+            if enemies_nearby && units.len() > 1 && cover == Some(u.id) {
+                (u.id, Role::Cover)
+            } else {
+                (u.id, Role::Looter)
+            }
+        })
+        .collect_vec()
+}
+
+fn inside_safe_zone(p: Vec2) -> Vec2 {
+    let zone = &get_game().zone;
+    let max_distance = zone.current_radius * SAFE_ZONE_RATIO;
+    if zone.current_center.distance(&p) <= max_distance {
+        p
+    } else {
+        rotate(
+            zone.current_center,
+            (p - zone.current_center).angle(),
+            max_distance,
+        )
+    }
+}
@@ -1,6 +1,7 @@
 use crate::debug_interface::DebugInterface;
 use crate::model::{Obstacle, Projectile, Unit, Vec2};
 use crate::strategy::holder::{get_constants, get_projectiles};
+use crate::strategy::shots::projectile_life_time;
 use itertools::Itertools;
 use std::cmp::min;
 
@@ -12,16 +13,37 @@ pub fn rotate(center: Vec2, angle: f64, distance: f64) -> Vec2 {
         }
 }
 
-pub fn intersects_with_obstacles_vec(v1: &Vec2, v2: &Vec2, obstacles: &Vec<Obstacle>) -> bool {
+pub fn intersects_with_obstacles_vec(v1: &Vec2, v2: &Vec2, obstacles: &[Obstacle]) -> bool {
     intersects_with_obstacles(v1.x, v1.y, v2.x, v2.y, obstacles)
 }
 
+// only the obstacles the filter keeps block the segment, e.g. the ones we can't see through
+pub fn intersects_with_obstacles_by(
+    v1: &Vec2,
+    v2: &Vec2,
+    obstacles: &[Obstacle],
+    blocks: fn(&Obstacle) -> bool,
+) -> bool {
+    intersects_with_blocking(v1.x, v1.y, v2.x, v2.y, obstacles, blocks)
+}
+
 pub fn intersects_with_obstacles(
     x1: f64,
     y1: f64,
     x2: f64,
     y2: f64,
-    obstacles: &Vec<Obstacle>,
+    obstacles: &[Obstacle],
+) -> bool {
+    intersects_with_blocking(x1, y1, x2, y2, obstacles, |o| !o.can_shoot_through)
+}
+
+fn intersects_with_blocking(
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+    obstacles: &[Obstacle],
+    blocks: fn(&Obstacle) -> bool,
 ) -> bool {
     for obs in obstacles.iter().filter(|o| {
         let min_x = if x1 < x2 { x1 } else { x2 } - o.radius;
@@ -40,7 +62,7 @@ pub fn intersects_with_obstacles(
         let dist = ((x2 - x1) * (y1 - y0) - (x1 - x0) * (y2 - y1)).abs()
             / ((x2 - x1).powf(2.0) + (y2 - y1).powf(2.0)).sqrt();
 
-        if dist < obs.radius && !obs.can_shoot_through {
+        if dist < obs.radius && blocks(obs) {
             return true;
         }
     }
@@ -78,7 +100,14 @@ pub fn intersects_with_units(x1: f64, y1: f64, x2: f64, y2: f64, units: &Vec<&Un
 }
 
 pub fn get_projectile_traces() -> Vec<Projectile> {
-    let mut current_projectiles = get_projectiles().clone();
+    // projectiles stopped by an obstacle or an enemy on the way don't reach us
+    let mut current_projectiles = get_projectiles()
+        .iter()
+        .map(|p| Projectile {
+            life_time: projectile_life_time(p),
+            ..p.clone()
+        })
+        .collect_vec();
     let mut traces = Vec::new();
     while let Some(projectile) = current_projectiles.pop() {
         let next_pos = match projectile.position_after_ticks(1) {
@@ -106,3 +135,8 @@ pub fn bullet_trace_score(bullets: &Vec<Projectile>, pos: &Vec2) -> f64 {
         .sum::<f64>()
         * 10000.0
 }
+
+pub fn centroid(points: impl Iterator<Item = Vec2>) -> Vec2 {
+    let points = points.collect_vec();
+    points.iter().fold(Vec2::default(), |acc, p| acc + *p) / points.len() as f64
+}
@@ -0,0 +1,152 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::TRANSPARENT_TEAL;
+use crate::model::ActionOrder::Aim;
+use crate::model::{Unit, UnitOrder, Vec2};
+use crate::strategy::holder::{
+    get_constants, get_enemy_beliefs, get_game, get_previous_behaviour, get_target, get_team_plan,
+    get_unexplained_sounds,
+};
+use crate::strategy::util::rotate;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+const DIRECTIONS: i32 = 16;
+const SEEN_ENEMY_WEIGHT: f64 = 1.0;
+// enemies we lost track of matter more, we may get surprised by them
+const LOST_ENEMY_WEIGHT: f64 = 2.0;
+const SOUND_WEIGHT: f64 = 1.5;
+// every direction around the team is worth a little, nobody should come from behind
+const BACKGROUND_WEIGHT: f64 = 0.2;
+const BACKGROUND_POINTS: i32 = 8;
+// looking against the movement makes the unit walk at the backward speed
+const BACKWARD_PENALTY: f64 = 0.5;
+// these behaviours face their target, turning the unit away would spoil the fight
+const FIGHTING_BEHAVIOURS: [&str; 3] = ["Fighting", "Kiting", "Peeking"];
+
+struct Watch {
+    position: Vec2,
+    weight: f64,
+}
+
+// Spreads look directions of free units so the team covers every threat, recent sound and,
+// when there is nothing else, its back. Units aiming or fighting keep facing their target.
+pub fn coordinate_vision(
+    orders: &mut HashMap<i32, UnitOrder>,
+    debug_interface: &mut Option<&mut DebugInterface>,
+) {
+    let units = get_game()
+        .my_units()
+        .into_iter()
+        .filter(|u| u.remaining_spawn_time.is_none())
+        .sorted_by_key(|u| u.id)
+        .collect_vec();
+    let mut watches = watches();
+    let (busy, free): (Vec<_>, Vec<_>) = units.into_iter().partition(|u| {
+        u.aim > 0.0
+            || orders
+                .get(&u.id)
+                .is_some_and(|o| matches!(o.action, Some(Aim { .. })))
+            || get_previous_behaviour(u.id).is_some_and(|b| FIGHTING_BEHAVIOURS.contains(&b))
+            || get_target(u.id).is_some()
+    });
+    for unit in busy {
+        let direction = orders
+            .get(&unit.id)
+            .map_or(unit.direction, |o| o.target_direction);
+        watches.retain(|w| !covers(unit, &direction, &w.position));
+    }
+
+    for unit in free {
+        let order = match orders.get_mut(&unit.id) {
+            None => continue,
+            Some(order) => order,
+        };
+        let best = (0..DIRECTIONS)
+            .map(|i| {
+                rotate(
+                    Vec2::default(),
+                    2.0 * PI * i as f64 / DIRECTIONS as f64,
+                    1.0,
+                )
+            })
+            .map(|direction| {
+                let covered = watches
+                    .iter()
+                    .filter(|w| covers(unit, &direction, &w.position))
+                    .map(|w| w.weight)
+                    .sum::<f64>();
+                (direction, covered - backward_penalty(order, &direction))
+            })
+            .max_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).unwrap());
+        if let Some((direction, score)) = best {
+            if score > 0.0 {
+                order.target_direction = direction;
+                watches.retain(|w| !covers(unit, &direction, &w.position));
+                if let Some(debug) = debug_interface.as_mut() {
+                    debug.add_segment(
+                        unit.position,
+                        unit.position + direction * 3.0,
+                        0.2,
+                        TRANSPARENT_TEAL,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn watches() -> Vec<Watch> {
+    let constants = get_constants();
+    let tick = get_game().current_tick;
+    let mut watches = get_enemy_beliefs()
+        .iter()
+        .filter(|b| b.unit.remaining_spawn_time.is_none())
+        .map(|b| Watch {
+            position: b.unit.position,
+            weight: if b.last_seen_tick == tick {
+                SEEN_ENEMY_WEIGHT
+            } else {
+                LOST_ENEMY_WEIGHT
+            },
+        })
+        .collect_vec();
+    watches.extend(get_unexplained_sounds().iter().map(|s| Watch {
+        position: s.position,
+        weight: SOUND_WEIGHT,
+    }));
+    let center = get_team_plan().regroup_point;
+    watches.extend((0..BACKGROUND_POINTS).map(|i| Watch {
+        position: rotate(
+            center,
+            2.0 * PI * i as f64 / BACKGROUND_POINTS as f64,
+            constants.view_distance * 0.7,
+        ),
+        weight: BACKGROUND_WEIGHT,
+    }));
+    watches
+}
+
+// the point falls into the view cone the unit would have looking in the direction
+fn covers(unit: &Unit, direction: &Vec2, p: &Vec2) -> bool {
+    let constants = get_constants();
+    if unit.position.distance(p) > constants.view_distance {
+        return false;
+    }
+    let (left, right) = unit.view_segment_angles();
+    let half_view = (right - left) / 2.0;
+    let mut difference = ((*p - unit.position).angle() - direction.angle()).abs();
+    if difference > PI {
+        difference = 2.0 * PI - difference;
+    }
+    difference <= half_view
+}
+
+fn backward_penalty(order: &UnitOrder, direction: &Vec2) -> f64 {
+    let velocity = order.target_velocity;
+    if velocity.len() < f64::EPSILON {
+        return 0.0;
+    }
+    let cos = (velocity.x * direction.x + velocity.y * direction.y) / velocity.len();
+    (1.0 - cos) / 2.0 * BACKWARD_PENALTY
+}
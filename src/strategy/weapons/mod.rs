@@ -0,0 +1,75 @@
+use crate::model::{Item, Loot, Unit, Vec2};
+use crate::strategy::cover::cover_score;
+use crate::strategy::holder::{get_all_enemy_units, get_constants, get_loot, get_target};
+use crate::strategy::loot::valuation::weapon_strength;
+use crate::strategy::targeting::fightable_enemies;
+
+// the other weapon has to be this much better to spend time on the swap
+const SWITCH_RATIO: f64 = 1.3;
+// how far from the fight we go for a better weapon
+const MAX_DETOUR: f64 = 6.0;
+// used when there is nobody to fight yet
+const DEFAULT_ENGAGEMENT_DISTANCE: f64 = 15.0;
+// cover against every threat we need to stand still looting
+const SAFE_COVER: f64 = 0.99;
+
+// strength of the weapon at the distance with the ammo we hold for it, less when out of range
+pub fn weapon_score(weapon_index: i32, unit: &Unit, distance: f64) -> f64 {
+    let weapon = &get_constants().weapons[weapon_index as usize];
+    let ammo = unit.ammo.get(weapon_index as usize).copied().unwrap_or(0);
+    let reachability = (weapon.firing_distance() / distance.max(f64::EPSILON)).min(1.0);
+    weapon_strength(weapon, ammo, distance) * reachability
+}
+
+// distance to the enemy the unit is about to fight
+pub fn engagement_distance(unit: &Unit) -> f64 {
+    if let Some(target) = get_target(unit.id) {
+        return unit.position.distance(&target.position);
+    }
+    fightable_enemies(unit)
+        .iter()
+        .map(|e| e.position.distance(&unit.position))
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+        .unwrap_or(DEFAULT_ENGAGEMENT_DISTANCE)
+}
+
+// weapon lying close enough which is clearly better than the one we hold
+pub fn better_weapon_nearby(unit: &Unit) -> Option<&'static Loot> {
+    let distance = engagement_distance(unit);
+    let current = unit.weapon.map_or(0.0, |w| weapon_score(w, unit, distance));
+    get_loot()
+        .iter()
+        .filter(|l| l.position.distance(&unit.position) < MAX_DETOUR)
+        .filter_map(|l| match l.item {
+            Item::Weapon { type_index } if unit.weapon != Some(type_index) => {
+                Some((l, weapon_score(type_index, unit, distance)))
+            }
+            _ => None,
+        })
+        .filter(|(_, score)| *score > current * SWITCH_RATIO && *score > 0.0)
+        .max_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).unwrap())
+        .map(|(l, _)| l)
+}
+
+// better weapon the unit can pick up without being shot while looting
+pub fn weapon_to_fetch(unit: &Unit) -> Option<&'static Loot> {
+    better_weapon_nearby(unit).filter(|l| is_safe_to_loot(&l.position))
+}
+
+// the pickup only goes through while the unit neither aims nor does anything else
+pub fn can_swap_now(unit: &Unit, loot: &Loot) -> bool {
+    unit.aim == 0.0
+        && unit.action.is_none()
+        && unit.position.distance(&loot.position) < get_constants().unit_radius
+        && is_safe_to_loot(&unit.position)
+}
+
+// nobody can shoot at the point or it is covered from everybody who can
+fn is_safe_to_loot(p: &Vec2) -> bool {
+    let threats = get_all_enemy_units()
+        .iter()
+        .filter(|e| e.remaining_spawn_time.is_none())
+        .filter(|e| e.position.distance(p) < e.firing_distance() + get_constants().unit_radius)
+        .collect::<Vec<_>>();
+    threats.is_empty() || cover_score(p, &threats) >= SAFE_COVER
+}
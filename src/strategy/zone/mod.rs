@@ -0,0 +1,146 @@
+use crate::debug_interface::DebugInterface;
+use crate::debugging::{TRANSPARENT_GREEN, TRANSPARENT_ORANGE};
+use crate::model::{Unit, Vec2};
+use crate::strategy::cover::all_around_cover;
+use crate::strategy::holder::{get_constants, get_game, get_zone_plan, set_zone_plan};
+use crate::strategy::util::{intersects_with_obstacles_vec, rotate};
+use itertools::Itertools;
+
+// how far ahead zone penalties and orbiting look
+pub const ZONE_LOOKAHEAD_SECONDS: f64 = 5.0;
+// the longest timeline we try to predict
+const HORIZON_SECONDS: f64 = 60.0;
+// extra time we want to be left when we reach safety
+const SAFETY_MARGIN_SECONDS: f64 = 3.0;
+// walking around obstacles is longer than the straight line
+const DETOUR_MODIFIER: f64 = 1.3;
+const FINAL_POSITION_CANDIDATES: i32 = 24;
+const COVER_WEIGHT: f64 = 3.0;
+
+#[derive(Clone, Debug)]
+pub struct ZonePlan {
+    // where the team wants to be when the zone gets small
+    pub final_position: Vec2,
+    // units which have to start moving to stay inside the zone
+    pub relocating: Vec<i32>,
+}
+
+impl ZonePlan {
+    pub const fn const_default() -> Self {
+        ZonePlan {
+            final_position: Vec2 { x: 0.0, y: 0.0 },
+            relocating: vec![],
+        }
+    }
+
+    pub fn is_relocating(&self, unit_id: i32) -> bool {
+        self.relocating.contains(&unit_id)
+    }
+}
+
+// zone moves towards the next center while the radius shrinks to the next radius
+pub fn predicted_zone(seconds: f64) -> (Vec2, f64) {
+    let zone = &get_game().zone;
+    let radius = (zone.current_radius - get_constants().zone_speed * seconds).max(0.0);
+    let shrink_left = zone.current_radius - zone.next_radius;
+    let progress = if shrink_left > 0.0 {
+        ((zone.current_radius - radius) / shrink_left).min(1.0)
+    } else {
+        1.0
+    };
+    let center = zone.current_center + (zone.next_center - zone.current_center) * progress;
+    (center, radius)
+}
+
+pub fn seconds_until_outside(p: &Vec2) -> f64 {
+    let mut seconds = 0.0;
+    while seconds < HORIZON_SECONDS {
+        let (center, radius) = predicted_zone(seconds);
+        if center.distance(p) + get_constants().unit_radius > radius {
+            return seconds;
+        }
+        seconds += 0.5;
+    }
+    HORIZON_SECONDS
+}
+
+pub fn seconds_to_reach(unit: &Unit, p: &Vec2) -> f64 {
+    let constants = get_constants();
+    let path_length = if intersects_with_obstacles_vec(&unit.position, p, &constants.obstacles) {
+        unit.position.distance(p) * DETOUR_MODIFIER
+    } else {
+        unit.position.distance(p)
+    };
+    path_length / constants.max_unit_forward_speed
+}
+
+// point on the orbit around the zone as it will be in a few seconds
+pub fn orbit_point(from: &Vec2) -> Vec2 {
+    let (center, radius) = predicted_zone(ZONE_LOOKAHEAD_SECONDS);
+    rotate(center, (*from - center).angle() + 0.1, radius * 0.85)
+}
+
+pub fn create_zone_plan(debug_interface: &mut Option<&mut DebugInterface>) {
+    let final_position = final_position();
+    let relocating = get_game()
+        .my_units()
+        .into_iter()
+        .filter(|u| {
+            seconds_until_outside(&u.position)
+                < seconds_to_reach(u, &final_position) + SAFETY_MARGIN_SECONDS
+        })
+        .map(|u| u.id)
+        .collect_vec();
+
+    if let Some(debug) = debug_interface.as_mut() {
+        let (center, radius) = predicted_zone(ZONE_LOOKAHEAD_SECONDS);
+        debug.add_ring(center, radius, 0.5, TRANSPARENT_ORANGE);
+        debug.add_circle(final_position, 1.5, TRANSPARENT_GREEN);
+    }
+    set_zone_plan(ZonePlan {
+        final_position,
+        relocating,
+    });
+}
+
+// spot with cover deep inside the next zone, keeps the previous one while it is good
+fn final_position() -> Vec2 {
+    let constants = get_constants();
+    let zone = &get_game().zone;
+    let safe_radius = zone.next_radius * 0.7;
+    let previous = get_zone_plan().final_position;
+    let is_free = |p: &Vec2| {
+        constants
+            .obstacles
+            .iter()
+            .all(|o| o.position.distance(p) > o.radius + constants.unit_radius)
+    };
+
+    let mut candidates = vec![zone.next_center];
+    if previous.distance(&zone.next_center) <= safe_radius {
+        candidates.push(previous);
+    }
+    for i in 0..FINAL_POSITION_CANDIDATES {
+        let angle = 2.0 * std::f64::consts::PI * i as f64 / FINAL_POSITION_CANDIDATES as f64;
+        candidates.push(rotate(zone.next_center, angle, safe_radius * 0.5));
+        candidates.push(rotate(zone.next_center, angle, safe_radius));
+    }
+    candidates
+        .into_iter()
+        .filter(is_free)
+        .map(|p| {
+            let cover = all_around_cover(&p) * COVER_WEIGHT;
+            let stability = if p.distance(&previous) < 1.0 {
+                1.0
+            } else {
+                0.0
+            };
+            (
+                p,
+                cover + stability - p.distance(&zone.next_center) / safe_radius.max(1.0),
+            )
+        })
+        .max_by(|(_, s1), (_, s2)| s1.partial_cmp(s2).unwrap())
+        .map(|(p, _)| p)
+        .unwrap_or(zone.next_center)
+}